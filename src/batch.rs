@@ -0,0 +1,45 @@
+use sudoku_rs::gameboard::Gameboard;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Number of placements that may be tried when checking single puzzle. Much more
+// than the game allows, since nobody waits for a frame here.
+const MAX_NODES: usize = 1_000_000;
+
+// Checks every puzzle in file, one per line, and prints whether it has unique
+// solution, together with that solution. Empty lines and lines starting with '#'
+// are skipped. Returns whether all puzzles were uniquely solvable.
+pub fn solve_all(path: &Path) -> io::Result<bool> {
+    let data = fs::read_to_string(path)?;
+    let mut all_unique = true;
+
+    for (n, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match unique_solution(line) {
+            Ok(solution) => println!("{}: unique {}", n + 1, solution),
+            Err(reason) => {
+                println!("{}: {}", n + 1, reason);
+                all_unique = false;
+            }
+        }
+    }
+
+    Ok(all_unique)
+}
+
+// Returns solution of puzzle, or reason why there isn't exactly one.
+fn unique_solution(line: &str) -> Result<Gameboard, String> {
+    let puzzle: Gameboard = line.parse().map_err(|err| format!("invalid puzzle: {}", err))?;
+
+    match puzzle.count_solutions(2, MAX_NODES) {
+        Some(1) => puzzle.solve_with_limit(MAX_NODES).map_err(|err| err.to_string()),
+        Some(0) => Err("no solution".to_string()),
+        Some(_) => Err("multiple solutions".to_string()),
+        None => Err("gave up, too hard to check".to_string())
+    }
+}
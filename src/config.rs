@@ -0,0 +1,237 @@
+use crate::ui::board::{GameboardViewSettigs, NoteLayout};
+use crate::ui::button::ButtonViewSettings;
+use std::fs;
+use std::path::PathBuf;
+
+// Size of board the default view settings are tuned for.
+const DEFAULT_BOARD_SIZE: f64 = 400.0;
+
+// Size of every button in the panel.
+const BUTTON_SIZE: [f64; 2] = [100.0, 30.0];
+
+// Vertical distance between tops of consecutive buttons.
+const BUTTON_SPACING: f64 = 40.0;
+
+// Stores settings of the whole application. Read from optional config file
+// given with `--config <path>`, made of `key = value` lines, and then from
+// command-line flags, e.g. `--board-size 500` for `board_size = 500`.
+pub struct AppConfig {
+    pub window_width: f64,
+    pub window_height: f64,
+    // Size of board along horizontal and vertical edge.
+    pub board_size: f64,
+    // Width of panel with buttons on the right side of window.
+    pub panel_width: f64,
+    // Font used instead of the embedded one.
+    pub font_path: Option<PathBuf>,
+    // Order of board to play on, None to keep the autosaved one.
+    pub order: Option<usize>,
+    // Seed of puzzle to start with, None to restore autosave or pick random one.
+    pub seed: Option<u64>,
+    // Whether autosave should be dropped and new game started.
+    pub new_game: bool,
+    // Whether digit keys refuse to replace a digit, so it must be erased first.
+    pub overwrite_protect: bool,
+    // Whether digits differing from solution are shown in another color.
+    pub highlight_wrong_entries: bool,
+    pub note_layout: NoteLayout,
+    // File with puzzle to play, optionally followed by its solution.
+    pub puzzle: Option<PathBuf>,
+    // File of puzzles to check without opening the window.
+    pub solve_all: Option<PathBuf>
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            window_width: 640.0,
+            window_height: 512.0,
+            board_size: DEFAULT_BOARD_SIZE,
+            panel_width: 128.0,
+            font_path: None,
+            order: None,
+            seed: None,
+            new_game: false,
+            overwrite_protect: false,
+            highlight_wrong_entries: true,
+            note_layout: NoteLayout::Grid,
+            puzzle: None,
+            solve_all: None
+        }
+    }
+}
+
+impl AppConfig {
+    // Builds config from command-line arguments, without program name.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut config = AppConfig::default();
+
+        if let Some(i) = args.iter().position(|arg| arg == "--config") {
+            let path = args.get(i + 1).ok_or("Missing path after --config")?;
+            let data = fs::read_to_string(path)
+                .map_err(|err| format!("Couldn't read config {}: {}", path, err))?;
+            config.apply_file(&data)?;
+        }
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let key = arg.strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument {}", arg))?;
+
+            match key {
+                "new" => config.new_game = true,
+                "config" => { args.next(); },
+                _ => {
+                    let value = args.next().ok_or_else(|| format!("Missing value after {}", arg))?;
+                    config.set(&key.replace('-', "_"), value)?;
+                }
+            }
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Position of board's top-left corner, centered in space left of the panel.
+    pub fn board_position(&self) -> [f64; 2] {
+        [
+            (self.window_width - self.panel_width - self.board_size) / 2.0,
+            (self.window_height - self.board_size) / 2.0
+        ]
+    }
+
+    // Returns view settings with board placed and fonts scaled to fit the window.
+    pub fn gameboard_view_settings(&self) -> GameboardViewSettigs {
+        let defaults = GameboardViewSettigs::default();
+        let scale = self.board_size / DEFAULT_BOARD_SIZE;
+
+        GameboardViewSettigs {
+            position: self.board_position(),
+            size: self.board_size,
+            font_size: (defaults.font_size as f64 * scale).round() as u32,
+            note_font_size: (defaults.note_font_size as f64 * scale).round() as u32,
+            highlight_wrong_entries: self.highlight_wrong_entries,
+            note_layout: self.note_layout,
+            ..defaults
+        }
+    }
+
+    // Returns settings of k-th button, with buttons stacked and centered
+    // between board and right edge of window.
+    pub fn button_view_settings(&self, k: usize, count: usize) -> ButtonViewSettings {
+        let board_right = self.board_position()[0] + self.board_size;
+        let stack_height = BUTTON_SPACING * (count - 1) as f64 + BUTTON_SIZE[1];
+
+        ButtonViewSettings::new([
+            (board_right + self.window_width - BUTTON_SIZE[0]) / 2.0,
+            (self.window_height - stack_height) / 2.0 + BUTTON_SPACING * k as f64
+        ], BUTTON_SIZE)
+    }
+
+    // Position of status line baseline, centered in space below board.
+    pub fn status_position(&self) -> [f64; 2] {
+        let position = self.board_position();
+        [position[0], position[1] + self.board_size + position[1] / 2.0 + 6.0]
+    }
+
+    fn apply_file(&mut self, data: &str) -> Result<(), String> {
+        for line in data.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=')
+                .ok_or_else(|| format!("Expected `key = value` in config, found `{}`", line))?;
+            self.set(key.trim(), value.trim())?;
+        }
+
+        Ok(())
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = || value.parse::<f64>().ok().filter(|number| *number > 0.0)
+            .ok_or_else(|| format!("{} must be a positive number", key));
+
+        match key {
+            "window_width" => self.window_width = number()?,
+            "window_height" => self.window_height = number()?,
+            "board_size" => self.board_size = number()?,
+            "panel_width" => self.panel_width = number()?,
+            "font" => self.font_path = Some(PathBuf::from(value)),
+            "puzzle" => self.puzzle = Some(PathBuf::from(value)),
+            "solve_all" => self.solve_all = Some(PathBuf::from(value)),
+            "size" => self.order = Some(match value {
+                "4" => 2,
+                "9" => 3,
+                "16" => 4,
+                _ => return Err("Supported board sizes are 4, 9 and 16".into())
+            }),
+            "overwrite_protect" => self.overwrite_protect = value.parse()
+                .map_err(|_| "overwrite_protect must be true or false".to_string())?,
+            "highlight_wrong_entries" => self.highlight_wrong_entries = value.parse()
+                .map_err(|_| "highlight_wrong_entries must be true or false".to_string())?,
+            "note_layout" => self.note_layout = match value {
+                "grid" => NoteLayout::Grid,
+                "row" => NoteLayout::Row,
+                _ => return Err("note_layout must be grid or row".into())
+            },
+            "seed" => self.seed = Some(value.parse()
+                .map_err(|_| "Seed must be a non-negative number".to_string())?),
+            _ => return Err(format!("Unknown setting {}", key))
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.board_size > self.window_height ||
+            self.board_size + self.panel_width > self.window_width {
+
+            return Err("Board and panel don't fit in the window".into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn flags_override_defaults() {
+        let config = AppConfig::from_args(&args("--board-size 300 --size 16 --seed 42 --new \
+            --overwrite-protect true --note-layout row --highlight-wrong-entries false")).unwrap();
+
+        assert_eq!(config.board_size, 300.0);
+        assert_eq!(config.order, Some(4));
+        assert_eq!(config.seed, Some(42));
+        assert!(config.new_game);
+        assert!(config.overwrite_protect);
+        assert_eq!(config.gameboard_view_settings().font_size, 26);
+        assert_eq!(config.gameboard_view_settings().note_layout, NoteLayout::Row);
+        assert!(!config.gameboard_view_settings().highlight_wrong_entries);
+    }
+
+    #[test]
+    fn default_layout_is_unchanged() {
+        let config = AppConfig::default();
+
+        assert_eq!(config.board_position(), [56.0, 56.0]);
+        assert_eq!(config.button_view_settings(0, 1).position, [498.0, 241.0]);
+    }
+
+    #[test]
+    fn bad_settings_are_rejected() {
+        assert!(AppConfig::from_args(&args("--size 5")).is_err());
+        assert!(AppConfig::from_args(&args("--board-size")).is_err());
+        assert!(AppConfig::from_args(&args("--board-size 600")).is_err());
+        assert!(AppConfig::from_args(&args("--colour red")).is_err());
+        assert!(AppConfig::from_args(&args("--overwrite-protect yes")).is_err());
+        assert!(AppConfig::from_args(&args("--note-layout column")).is_err());
+    }
+}
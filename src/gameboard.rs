@@ -1,400 +1,1217 @@
-use graphics::{Graphics, character::CharacterCache, Context, types::Color};
-use piston::generic_event::GenericEvent;
-use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use crate::logic::{self, Difficulty};
+use crate::rng::Rng;
+use crate::solver::Grid;
 
-// Size of gameboard.
-const SIZE: usize = 9;
+// Largest supported size of gameboard.
+pub const MAX_SIZE: usize = 16;
+
+// Supported orders (side lengths of a single box).
+pub const ORDERS: [usize; 3] = [2, 3, 4];
+
+// Number of placements that may be tried when checking that solution is unique.
+const UNIQUENESS_NODES: usize = 2000;
+
+// First line of serialized board, carrying format version.
+const SAVE_HEADER: &str = "sudoku-rs 1";
+
+// Returns character representing digit. Digits above 9 are written as letters,
+// starting with 'A' for 10.
+pub fn digit_char(val: u8) -> char {
+    match val {
+        1..=9 => (b'0' + val) as char,
+        10..=16 => (b'A' + val - 10) as char,
+        _ => '0'    // Should never happen.
+    }
+}
+
+// Returns digit represented by character, inverse of `digit_char`.
+pub fn char_digit(ch: char) -> Option<u8> {
+    match ch {
+        '1'..='9' => Some(ch as u8 - b'0'),
+        'A'..='G' => Some(ch as u8 - b'A' + 10),
+        _ => None
+    }
+}
+
+// Describes who wrote digit in cell.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Origin {
+    // Part of the puzzle, can't be changed.
+    Given,
+    // Entered by the player.
+    #[default]
+    Player,
+    // Filled by hint or solver.
+    Hint
+}
+
+// Reason why solver didn't return a solution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    NoSolution,
+    // Placement limit was reached before solution was found.
+    GaveUp
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::NoSolution => write!(f, "no solution"),
+            SolveError::GaveUp => write!(f, "solver gave up")
+        }
+    }
+}
+
+// Outcome of checking board filled so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckReport {
+    // Whether every cell holds a digit.
+    pub complete: bool,
+    // Cells whose digit repeats in their row, column or box.
+    pub conflicts: Vec<[usize; 2]>,
+    // Number of cells holding a digit, givens included.
+    pub filled: usize,
+    // Number of cells left to fill.
+    pub empty: usize
+}
+
+impl CheckReport {
+    pub fn is_solved(&self) -> bool {
+        self.complete && self.conflicts.is_empty()
+    }
+}
+
+// Change of board, reported to whoever watches the game.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BoardEvent {
+    // Digit in cell changed, None means empty cell.
+    CellSet { ind: [usize; 2], old: Option<u8>, new: Option<u8> },
+    // Digit got noted in cell, or note got removed.
+    NoteToggled { ind: [usize; 2], digit: u8 },
+    // Board got filled without conflicts.
+    Solved
+}
 
 // Stores information about single cell.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 struct Cell {
     digit: u8, // 0 means no digit is written.
-    notes: [bool; 9] // Describes which digit is pencil-marked in the cell.
+    notes: [bool; MAX_SIZE], // Describes which digit is pencil-marked in the cell.
+    origin: Origin, // Who wrote the digit. Meaningless for empty cell.
+    notes_locked: bool // Whether notes are protected from writing a digit over them.
 }
 
-impl Default for Cell {
-    fn default() -> Self {
-        Self {
-            digit: 0,
-            notes: [false; 9]
-        }
+impl Cell {
+    fn is_given(&self) -> bool {
+        self.origin == Origin::Given
     }
 }
 
 // Stores information about game board.
+#[derive(Clone)]
 pub struct Gameboard {
+    // Side length of a single box. Board is order^2 cells wide.
+    order: usize,
     // Contents of cells.
     // 0 means empty cell.
-    cells: [[Cell; SIZE]; SIZE],
-    selected_cell: Option<[usize; 2]>
+    cells: Vec<Vec<Cell>>,
+    // Whether notes of empty cells are kept equal to their candidates automatically.
+    auto_candidates: bool
 }
 
 impl Gameboard {
-    pub fn new() -> Self {
+    // Creates board made of order x order boxes, each order x order cells.
+    pub fn with_order(order: usize) -> Self {
+        assert!(ORDERS.contains(&order), "Unsupported board order: {}", order);
+
+        let size = order * order;
         Self {
-            cells: [[Cell::default(); SIZE]; SIZE],
-            selected_cell: None
+            order,
+            cells: vec![vec![Cell::default(); size]; size],
+            auto_candidates: false
         }
     }
 
-    // Returns digit written in cell.
-    pub fn get_digit(&self, ind: [usize; 2]) -> Option<u8> {
-        let digit = self.cells[ind[1]][ind[0]].digit;
+    // Generates classic 9x9 puzzle with given number of clues.
+    // Same seed and clue count always produce the same puzzle.
+    pub fn generate_seeded(clues: usize, seed: u64) -> Self {
+        Self::generate_seeded_with_order(3, clues, seed)
+    }
 
-        if digit == 0 {
-            None
-        } else {
-            Some(digit)
+    // Generates puzzle of given order with unique solution. Clues are removed from
+    // random full grid for as long as solution provably stays unique, so puzzle
+    // may end up with more clues than requested.
+    pub fn generate_seeded_with_order(order: usize, clues: usize, seed: u64) -> Self {
+        let mut gameboard = Gameboard::with_order(order);
+        let size = gameboard.size();
+        let mut rng = Rng::new(seed);
+
+        let mut grid = Grid::new(order);
+        grid.fill(Some(&mut rng), usize::MAX);
+
+        let mut cells: Vec<usize> = (0..size * size).collect();
+        rng.shuffle(&mut cells);
+
+        let mut remaining = cells.len();
+        for i in cells {
+            if remaining <= clues {
+                break;
+            }
+
+            let digit = grid.get(i);
+            grid.clear(i);
+            if grid.count_solutions(2, UNIQUENESS_NODES) == Some(1) {
+                remaining -= 1;
+            } else {
+                grid.place(i, digit);
+            }
+        }
+
+        for (i, cell) in gameboard.cells.iter_mut().flatten().enumerate() {
+            cell.digit = grid.get(i);
+            if cell.digit != 0 {
+                cell.origin = Origin::Given;
+            }
         }
+
+        gameboard
     }
 
-    // Returns notes put in cell.
-    pub fn get_notes(&self, ind: [usize; 2]) -> [bool; 9] {
-        return self.cells[ind[1]][ind[0]].notes;
+    // Returns board with all cells filled according to unique solution of the puzzle
+    // formed by given digits. Returns None if there are no givens, or solution
+    // isn't unique or couldn't be proven unique quickly.
+    pub fn solution(&self) -> Option<Gameboard> {
+        if self.count_solutions(2, UNIQUENESS_NODES) != Some(1) {
+            return None;
+        }
+
+        self.solve_with_limit(UNIQUENESS_NODES).ok()
     }
 
-    // Writes single digit in cell.
-    pub fn set(&mut self, ind: [usize; 2], val: u8) {
-        self.cells[ind[1]][ind[0]].digit = val;
+    // Counts solutions of the puzzle formed by given digits, stopping once limit
+    // is reached. Returns None if search gave up after max_nodes placements.
+    pub fn count_solutions(&self, limit: usize, max_nodes: usize) -> Option<usize> {
+        match self.givens_grid() {
+            Some(mut grid) => grid.count_solutions(limit, max_nodes),
+            None => Some(0)
+        }
     }
 
-    // Notes digit in cell. If digit is already noted, removes it.
-    pub fn note(&mut self, ind: [usize; 2], val: u8) {
-        let ref mut cell = self.cells[ind[1]][ind[0]];
-        let i = (val - 1) as usize;
-        cell.notes[i] = !cell.notes[i];
+    // Returns board filled with first solution found for the puzzle formed by
+    // given digits, None if there is no solution. Searches without limit.
+    pub fn solve(&self) -> Option<Gameboard> {
+        self.solve_with_limit(usize::MAX).ok()
     }
-}
 
-// Stores settings for game board view.
-pub struct GameboardViewSettigs {
-    // Position from top-left corner.
-    pub position: [f64; 2],
-    // Size along horizontal and vertical edge.
-    pub size: f64,
-    // Color of background.
-    pub background_color: Color,
-    // Color of board border.
-    pub border_color: Color,
-    // Color of edge around board.
-    pub board_edge_color: Color,
-    // Color of edge around 3x3 section.
-    pub section_edge_color: Color,
-    // Color of edge around single cell.
-    pub cell_edge_color: Color,
-    // Backgrond color of selected cell.
-    pub selected_cell_background_color: Color,
-    // Radius of edge around board.
-    pub board_edge_radius: f64,
-    // Radius of edge around 3x3 section.
-    pub section_edge_radius: f64,
-    // Radius of edge around single cell.
-    pub cell_edge_radius: f64,
-    // Color of font.
-    pub text_color: Color,
-    // Size of font.
-    pub font_size: u32,
-    // Color of font for notes.
-    pub note_color: Color,
-    // Size of font for notes.
-    pub note_font_size: u32
-}
+    // Same as `solve`, but gives up after trying max_nodes placements.
+    pub fn solve_with_limit(&self, max_nodes: usize) -> Result<Gameboard, SolveError> {
+        let mut grid = self.givens_grid().ok_or(SolveError::NoSolution)?;
+        match grid.fill(None, max_nodes) {
+            Some(true) => (),
+            Some(false) => return Err(SolveError::NoSolution),
+            None => return Err(SolveError::GaveUp)
+        }
 
-impl Default for GameboardViewSettigs {
-    fn default() -> Self {
-        Self {
-            position: [56.0; 2],
-            size: 400.0,
-            background_color: [0.8, 0.8, 1.0, 1.0],
-            border_color: [0.0, 0.0, 0.2, 1.0],
-            board_edge_color: [0.0, 0.0, 0.2, 1.0],
-            section_edge_color: [0.0, 0.0, 0.2, 1.0],
-            cell_edge_color: [0.0, 0.0, 0.2, 1.0],
-            selected_cell_background_color: [0.9, 0.9, 1.0, 1.0],
-            board_edge_radius: 3.0,
-            section_edge_radius: 2.0,
-            cell_edge_radius: 1.0,
-            text_color: [0.0, 0.0, 1.0, 1.0],
-            font_size: 34,
-            note_color: [0.37, 0.37, 0.63, 1.0],
-            note_font_size: 10
+        let size = self.size();
+        let mut solution = Gameboard::with_order(self.order);
+        for (i, cell) in solution.cells.iter_mut().flatten().enumerate() {
+            cell.digit = grid.get(i);
+            cell.origin = if self.cells[i / size][i % size].is_given() {
+                Origin::Given
+            } else {
+                Origin::Hint
+            };
         }
+
+        Ok(solution)
     }
-}
 
-pub struct GameboardView {
-    settings: GameboardViewSettigs
-}
+    // Returns changes turning earlier state of the same board into this one,
+    // row by row.
+    pub fn changes_since(&self, before: &Gameboard) -> Vec<BoardEvent> {
+        let size = self.size();
+        let mut events = Vec::new();
 
-impl GameboardView {
-    pub fn new(settings: GameboardViewSettigs) -> Self {
-        Self {
-            settings
+        for ind in (0..size * size).map(|i| [i % size, i / size]) {
+            let (old, new) = (before.get_digit(ind), self.get_digit(ind));
+            if old != new {
+                events.push(BoardEvent::CellSet { ind, old, new });
+            }
+
+            let toggled = before.get_notes(ind).iter().zip(self.get_notes(ind))
+                .enumerate()
+                .filter(|(_, (was, is))| was != is);
+            for (i, _) in toggled {
+                events.push(BoardEvent::NoteToggled { ind, digit: i as u8 + 1 });
+            }
         }
+
+        if self.is_complete() && !before.is_complete() {
+            events.push(BoardEvent::Solved);
+        }
+
+        events
     }
 
-    pub fn draw<G, C>(&mut self, gameboard: &Gameboard, c: &Context, g: &mut G, glyphs: &mut C)
-        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
-        use graphics::*;
+    // Returns grid holding given digits, None if they repeat within a unit.
+    fn givens_grid(&self) -> Option<Grid> {
+        let mut grid = Grid::new(self.order);
 
-        let ref settings = self.settings;
-        let cell_size = settings.size / 9.0;
-        let board_rect = [
-            settings.position[0], settings.position[1],
-            settings.size, settings.size
-        ];
+        for (i, cell) in self.cells.iter().flatten().enumerate() {
+            if cell.is_given() {
+                if !grid.can_place(i, cell.digit) {
+                    return None;
+                }
+                grid.place(i, cell.digit);
+            }
+        }
 
-        // Draw board background.
-        Rectangle::new(settings.background_color)
-            .draw(board_rect, &c.draw_state, c.transform, g);
+        Some(grid)
+    }
 
-        // Draw selected cell background.
-        if let Some(ind) = gameboard.selected_cell {
-            let pos = [ind[0] as f64 * cell_size, ind[1] as f64 * cell_size];
-            let cell_rect = [
-                settings.position[0] + pos[0], settings.position[1] + pos[1],
-                cell_size, cell_size
-            ];
+    // Returns copy of board holding only given digits.
+    pub fn puzzle(&self) -> Gameboard {
+        let mut puzzle = Gameboard::with_order(self.order);
+        for (cell, original) in puzzle.cells.iter_mut().flatten().zip(self.cells.iter().flatten()) {
+            if original.is_given() {
+                cell.digit = original.digit;
+                cell.origin = Origin::Given;
+            }
+        }
 
-            Rectangle::new(settings.selected_cell_background_color)
-                .draw(cell_rect, &c.draw_state, c.transform, g);
+        puzzle
+    }
+
+    // Rates puzzle formed by given digits with hardest technique needed to solve it.
+    pub fn difficulty(&self) -> Difficulty {
+        logic::solve(&self.puzzle()).difficulty()
+    }
+
+    // Returns side length of a single box.
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    // Returns number of cells along board edge, which is also the largest digit.
+    pub fn size(&self) -> usize {
+        self.order * self.order
+    }
+
+    // Returns digit written in cell, None if it's empty or outside of board.
+    pub fn get_digit(&self, ind: [usize; 2]) -> Option<u8> {
+        self.cell(ind).map(|cell| cell.digit).filter(|&digit| digit != 0)
+    }
+
+    // Returns notes put in cell, or no notes for cells outside of board.
+    pub fn get_notes(&self, ind: [usize; 2]) -> &[bool] {
+        let size = self.size();
+        self.cell(ind).map_or(&[], |cell| &cell.notes[..size])
+    }
+
+    // Returns whether cell holds a digit given by the puzzle.
+    pub fn is_given(&self, ind: [usize; 2]) -> bool {
+        self.cell(ind).is_some_and(|cell| cell.is_given())
+    }
+
+    // Returns whether notes of cell are locked.
+    pub fn is_notes_locked(&self, ind: [usize; 2]) -> bool {
+        self.cell(ind).is_some_and(|cell| cell.notes_locked)
+    }
+
+    // Locks notes of cell, so no digit can be written in it, or unlocks them.
+    // Given cells and cells outside of board are ignored.
+    pub fn toggle_notes_lock(&mut self, ind: [usize; 2]) {
+        match self.cell_mut(ind) {
+            Some(cell) if !cell.is_given() => cell.notes_locked = !cell.notes_locked,
+            _ => ()
         }
+    }
 
-        // Draw digits.
-        for j in 0..9 {
-            for i in 0..9 {
-                let pos = [
-                    settings.position[0] + i as f64 * cell_size,
-                    settings.position[1] + j as f64 * cell_size
-                ];
+    // Returns who wrote digit in cell, None if it's empty or outside of board.
+    pub fn origin(&self, ind: [usize; 2]) -> Option<Origin> {
+        self.cell(ind).filter(|cell| cell.digit != 0).map(|cell| cell.origin)
+    }
 
-                if let Some(digit) = gameboard.get_digit([i, j]) {
-                    let text_image = Image::new_color(settings.text_color);
-                    if let Ok(character) = glyphs.character(settings.font_size,
-                        GameboardView::get_char(digit)) {
-
-                        let ch_x = pos[0] + (cell_size - character.atlas_size[0]) / 2.0;
-                        let ch_y = pos[1] + (cell_size - character.atlas_size[1]) / 2.0;
-
-                        let text_image = text_image.src_rect([
-                            character.atlas_offset[0],
-                            character.atlas_offset[1],
-                            character.atlas_size[0],
-                            character.atlas_size[1]
-                        ]);
-
-                        let transform = c.transform.trans(ch_x, ch_y);
-                        text_image.draw(character.texture, &c.draw_state, transform, g);
-                    }
-                } else {
-                    let notes = gameboard.get_notes([i, j]);
-                    let text_image = Image::new_color(settings.note_color);
-                    for n in 0..9 {
-                        if notes[n] {
-                            if let Ok(character) = glyphs.character(settings.note_font_size,
-                                GameboardView::get_char((n + 1) as u8)) {
-
-                                // let ch_x = pos[0] + cell_size / 6.0 - character.atlas_size[0] / 2.0 + cell_size / 3.0 * (n % 3) as f64;
-                                // let ch_y = pos[1] + cell_size / 6.0 - character.atlas_size[1] / 2.0 + cell_size / 3.0 * (n / 3) as f64;
-
-                                let ch_x = pos[0] + cell_size / 3.0 * (0.5 + (n % 3) as f64)
-                                    - character.atlas_size[0] / 2.0;
-                                let ch_y = pos[1] + cell_size / 3.0 * (0.5 + (n / 3) as f64)
-                                    - character.atlas_size[1] / 2.0;
-
-                                let text_image = text_image.src_rect([
-                                    character.atlas_offset[0],
-                                    character.atlas_offset[1],
-                                    character.atlas_size[0],
-                                    character.atlas_size[1]
-                                ]);
-
-                                let transform = c.transform.trans(ch_x, ch_y);
-                                text_image.draw(character.texture, &c.draw_state, transform, g);
-                            }
-                        }
-                    }
-                }
-            }
+    // Writes single digit in cell as entered by the player, 0 erases it. Given cells,
+    // cells with locked notes, cells outside of board and digits too large for board
+    // are ignored. With auto candidates on, notes of peers are updated to match.
+    pub fn set(&mut self, ind: [usize; 2], val: u8) {
+        self.place(ind, val, Origin::Player);
+    }
+
+    // Same as `set`, but records digit as written by hint or solver.
+    pub fn set_hint(&mut self, ind: [usize; 2], val: u8) {
+        self.place(ind, val, Origin::Hint);
+    }
+
+    fn place(&mut self, ind: [usize; 2], val: u8, origin: Origin) {
+        if val as usize > self.size() {
+            return;
         }
 
-        // Draw grid.
-        let cell_edge = Line::new(settings.cell_edge_color, settings.cell_edge_radius);
-        let section_edge = Line::new(settings.section_edge_color, settings.section_edge_radius);
+        let cell = match self.cell_mut(ind) {
+            Some(cell) if !cell.is_given() && !cell.notes_locked => cell,
+            _ => return
+        };
 
-        for i in 0..9 {
-            let x = settings.position[0] + i as f64 / 9.0 * settings.size;
-            let y = settings.position[1] + i as f64 / 9.0 * settings.size;
-            let x2 = settings.position[0] + settings.size;
-            let y2 = settings.position[1] + settings.size;
+        let old = cell.digit;
+        cell.digit = val;
+        cell.origin = origin;
 
-            let vline = [x, settings.position[1], x, y2];
-            let hline = [settings.position[0], y, x2, y];
+        if self.auto_candidates && old != val {
+            self.update_candidates(ind, old, val);
+        }
+    }
 
-            if i % 3 == 0 {
-                section_edge.draw(vline, &c.draw_state, c.transform, g);
-                section_edge.draw(hline, &c.draw_state, c.transform, g);
-            } else {
-                cell_edge.draw(vline, &c.draw_state, c.transform, g);
-                cell_edge.draw(hline, &c.draw_state, c.transform, g);
+    // Returns cells whose digit repeats in their row, column or box.
+    pub fn find_conflicts(&self) -> Vec<[usize; 2]> {
+        let size = self.size();
+        let mut conflicts = Vec::new();
+
+        for row in 0..size {
+            for column in 0..size {
+                let digit = self.cells[row][column].digit;
+                if digit != 0 && self.peers([column, row]).iter()
+                    .any(|peer| self.cells[peer[1]][peer[0]].digit == digit) {
+
+                    conflicts.push([column, row]);
+                }
             }
         }
 
-        // Draw board edge.
-        Rectangle::new_border(settings.board_edge_color, settings.board_edge_radius)
-            .draw(board_rect, &c.draw_state, c.transform, g);
+        conflicts
+    }
+
+    // Returns whether no digit repeats in any row, column or box.
+    // Empty cells don't make board invalid.
+    pub fn is_valid(&self) -> bool {
+        self.find_conflicts().is_empty()
     }
 
-    fn get_char(val: u8) -> char {
-        match val {
-            1 => '1',
-            2 => '2',
-            3 => '3',
-            4 => '4',
-            5 => '5',
-            6 => '6',
-            7 => '7',
-            8 => '8',
-            9 => '9',
-            _ => '0'    // Should never happen.
+    // Returns whether every cell is filled and board is valid.
+    pub fn is_complete(&self) -> bool {
+        self.empty_cells() == 0 && self.is_valid()
+    }
+
+    // Returns conflicts and progress of board, as shown to player on check.
+    pub fn check(&self) -> CheckReport {
+        let empty = self.empty_cells();
+
+        CheckReport {
+            complete: empty == 0,
+            conflicts: self.find_conflicts(),
+            filled: self.size() * self.size() - empty,
+            empty
         }
     }
-}
 
-pub struct GameboardController {
-    gameboard: Gameboard,
-    gameboard_view: GameboardView,
-    cursor_pos: [f64; 2],
-    shift_pressed: bool
-}
+    // Returns number of cells without digit.
+    pub fn empty_cells(&self) -> usize {
+        self.cells.iter().flatten().filter(|cell| cell.digit == 0).count()
+    }
 
-impl GameboardController {
-    pub fn new(gameboard: Gameboard, gameboard_view: GameboardView) -> Self {
-        Self {
-            gameboard,
-            gameboard_view,
-            cursor_pos: [0.0; 2],
-            shift_pressed: false
+    // Removes all notes, leaving digits untouched.
+    pub fn clear_all_notes(&mut self) {
+        for cell in self.cells.iter_mut().flatten() {
+            cell.notes = [false; MAX_SIZE];
         }
     }
 
-    pub fn check(&self) -> bool {
-        let ref gameboard = self.gameboard;
+    pub fn auto_candidates(&self) -> bool {
+        self.auto_candidates
+    }
 
-        let mut occurrences = BTreeSet::new();
+    // Turns automatic candidates on or off. Turning them on replaces notes of
+    // all empty cells with their candidates.
+    pub fn set_auto_candidates(&mut self, on: bool) {
+        self.auto_candidates = on;
 
-        for row in 0..9 {
-            occurrences.clear();
-            for column in 0..9 {
-                let digit = gameboard.cells[row][column].digit;
-                if digit == 0 || occurrences.contains(&digit) {
-                    return false;
-                } else {
-                    occurrences.insert(digit);
+        if on {
+            let size = self.size();
+            for row in 0..size {
+                for column in 0..size {
+                    let cell = &self.cells[row][column];
+                    if cell.digit == 0 && !cell.notes_locked {
+                        self.cells[row][column].notes = self.compute_candidates([column, row]);
+                    }
                 }
             }
         }
+    }
 
-        for column in 0..9 {
-            occurrences.clear();
-            for row in 0..9 {
-                let digit = gameboard.cells[row][column].digit;
-                if occurrences.contains(&digit) {
-                    return false;
-                } else {
-                    occurrences.insert(digit);
-                }
+    // Returns which digits can be written in cell without repeating
+    // a digit of any peer. Meaningful for empty cells only.
+    pub fn compute_candidates(&self, ind: [usize; 2]) -> [bool; MAX_SIZE] {
+        let mut candidates = [false; MAX_SIZE];
+        for candidate in candidates.iter_mut().take(self.size()) {
+            *candidate = true;
+        }
+
+        for peer in self.peers(ind) {
+            let digit = self.cells[peer[1]][peer[0]].digit;
+            if digit != 0 {
+                candidates[(digit - 1) as usize] = false;
             }
         }
 
-        for section in 0..9 {
-            occurrences.clear();
-            for i in 0..9 {
-                let column = (section % 3) * 3 + i % 3;
-                let row = (section / 3) * 3 + i / 3;
-                let digit = gameboard.cells[row][column].digit;
-                if occurrences.contains(&digit) {
-                    return false;
-                } else {
-                    occurrences.insert(digit);
+        candidates
+    }
+
+    // Fills every empty cell with only one candidate left, repeating until no such
+    // cell remains. Cells with locked notes are skipped, and nothing is filled on
+    // board with conflicts, as its candidates prove nothing. Returns number of filled cells.
+    pub fn solve_naked_singles(&mut self) -> usize {
+        if !self.is_valid() {
+            return 0;
+        }
+
+        let size = self.size();
+        let mut filled = 0;
+        loop {
+            let before = filled;
+            for row in 0..size {
+                for column in 0..size {
+                    let cell = &self.cells[row][column];
+                    if cell.digit != 0 || cell.notes_locked {
+                        continue;
+                    }
+
+                    let candidates = self.compute_candidates([column, row]);
+                    let mut digits = (1..=size as u8).filter(|&digit| candidates[(digit - 1) as usize]);
+                    if let (Some(digit), None) = (digits.next(), digits.next()) {
+                        self.set_hint([column, row], digit);
+                        filled += 1;
+                    }
                 }
             }
+
+            if filled == before {
+                return filled;
+            }
         }
+    }
 
-        true
+    // Returns row, column and box containing cell, which are full and hold no
+    // repeated digit.
+    pub fn completed_units(&self, ind: [usize; 2]) -> Vec<Vec<[usize; 2]>> {
+        let order = self.order;
+        let size = self.size();
+        let corner = [ind[0] / order * order, ind[1] / order * order];
+
+        let units: [Vec<[usize; 2]>; 3] = [
+            (0..size).map(|k| [k, ind[1]]).collect(),
+            (0..size).map(|k| [ind[0], k]).collect(),
+            (0..size).map(|k| [corner[0] + k % order, corner[1] + k / order]).collect()
+        ];
+
+        units.iter().filter(|unit| {
+            let mut seen = [false; MAX_SIZE];
+            unit.iter().all(|&[column, row]| {
+                let digit = self.cells[row][column].digit as usize;
+                digit != 0 && !std::mem::replace(&mut seen[digit - 1], true)
+            })
+        }).cloned().collect()
     }
 
-    pub fn draw<G, C>(&mut self, c: &Context, g: &mut G, glyphs: &mut C)
-        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
+    // Returns cells sharing row, column or box with given cell, excluding itself.
+    pub fn peers(&self, ind: [usize; 2]) -> Vec<[usize; 2]> {
+        let order = self.order;
+        let size = self.size();
+        let corner = [ind[0] / order * order, ind[1] / order * order];
+
+        let mut peers = Vec::new();
+        for k in 0..size {
+            peers.push([k, ind[1]]);
+            peers.push([ind[0], k]);
+            peers.push([corner[0] + k % order, corner[1] + k / order]);
+        }
 
-        self.gameboard_view.draw(&self.gameboard, c, g, glyphs);
+        peers.sort_unstable();
+        peers.dedup();
+        peers.retain(|&peer| peer != ind);
+        peers
     }
 
-    pub fn handle_event<E>(&mut self, e: &E) where E: GenericEvent {
-        use piston::input::*;
+    // Keeps notes consistent after digit in cell changed from old to new:
+    // new digit stops being a candidate of peers, old one becomes candidate
+    // again where it's legal, and emptied cell gets its candidates noted.
+    // Locked notes are left as they are.
+    fn update_candidates(&mut self, ind: [usize; 2], old: u8, new: u8) {
+        for peer in self.peers(ind) {
+            let cell = &self.cells[peer[1]][peer[0]];
+            if cell.digit != 0 || cell.notes_locked {
+                continue;
+            }
 
-        let pos = self.gameboard_view.settings.position;
-        let size = self.gameboard_view.settings.size;
+            if new != 0 {
+                self.cells[peer[1]][peer[0]].notes[(new - 1) as usize] = false;
+            }
+            if old != 0 && self.compute_candidates(peer)[(old - 1) as usize] {
+                self.cells[peer[1]][peer[0]].notes[(old - 1) as usize] = true;
+            }
+        }
 
-        if let Some(pos) = e.mouse_cursor_args() {
-            self.cursor_pos = pos;
+        if new == 0 {
+            self.cells[ind[1]][ind[0]].notes = self.compute_candidates(ind);
         }
+    }
 
-        if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
-            // Find coordinates relative to top-left corner.
-            let x = self.cursor_pos[0] - pos[0];
-            let y = self.cursor_pos[1] - pos[1];
+    // Notes digit in cell. If digit is already noted, removes it.
+    // Notes are hidden under a digit, so digit in cell is first erased and kept
+    // as a note, and noted digit is then added rather than toggled, as notes
+    // uncovered by erasing (e.g. automatic candidates) could already hold it.
+    // Given cells, cells outside of board and digits outside of 1..=size are ignored.
+    pub fn note(&mut self, ind: [usize; 2], val: u8) {
+        if val == 0 || val as usize > self.size() {
+            return;
+        }
 
-            if x >= 0.0 && x < size && y >= 0.0 && y < size {
-                let cell_x = (x / size * 9.0) as usize;
-                let cell_y = (y / size * 9.0) as usize;
+        let old = match self.cell(ind) {
+            Some(cell) if !cell.is_given() => cell.digit,
+            _ => return
+        };
 
-                self.gameboard.selected_cell = Some([cell_x, cell_y]);
+        if old != 0 {
+            self.set(ind, 0);
+            if self.get_digit(ind).is_some() {
+                // Digit stays because notes are locked.
+                return;
             }
+
+            let notes = &mut self.cells[ind[1]][ind[0]].notes;
+            notes[(old - 1) as usize] = true;
+            notes[(val - 1) as usize] = true;
+            return;
+        }
+
+        let notes = &mut self.cells[ind[1]][ind[0]].notes;
+        notes[(val - 1) as usize] = !notes[(val - 1) as usize];
+    }
+
+    // Clears digit and notes of cell. With automatic candidates notes are reset
+    // to candidates instead. Given cells and cells with locked notes are ignored.
+    pub fn erase(&mut self, ind: [usize; 2]) {
+        if self.is_given(ind) || self.is_notes_locked(ind) || self.cell(ind).is_none() {
+            return;
+        }
+
+        self.set(ind, 0);
+        self.cells[ind[1]][ind[0]].notes = if self.auto_candidates {
+            self.compute_candidates(ind)
+        } else {
+            [false; MAX_SIZE]
+        };
+    }
+
+    // Returns cell at given coordinates, None if they're outside of board.
+    fn cell(&self, ind: [usize; 2]) -> Option<&Cell> {
+        self.cells.get(ind[1]).and_then(|row| row.get(ind[0]))
+    }
+
+    fn cell_mut(&mut self, ind: [usize; 2]) -> Option<&mut Cell> {
+        self.cells.get_mut(ind[1]).and_then(|row| row.get_mut(ind[0]))
+    }
+
+    // Writes board in save format: header line, order line and one line per row.
+    // Each cell is a digit, followed by '!' if given or '?' if filled by hint,
+    // or '.' for empty cell.
+    // Then comes '#' if notes are locked and noted digits, if there are any, after ':'.
+    pub fn serialize(&self) -> String {
+        let mut out = format!("{}\n{}\n", SAVE_HEADER, self.order);
+
+        for row in &self.cells {
+            let tokens: Vec<String> = row.iter().map(|cell| {
+                let mut token = String::new();
+                if cell.digit == 0 {
+                    token.push('.');
+                } else {
+                    token.push(digit_char(cell.digit));
+                    match cell.origin {
+                        Origin::Given => token.push('!'),
+                        Origin::Hint => token.push('?'),
+                        Origin::Player => ()
+                    }
+                }
+                if cell.notes_locked {
+                    token.push('#');
+                }
+
+                let notes: String = (1..=self.size() as u8)
+                    .filter(|&d| cell.notes[(d - 1) as usize])
+                    .map(digit_char)
+                    .collect();
+                if !notes.is_empty() {
+                    token.push(':');
+                    token.push_str(&notes);
+                }
+
+                token
+            }).collect();
+
+            out.push_str(&tokens.join(" "));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    // Reads board written by `serialize`. Returns None if data is malformed.
+    pub fn deserialize(data: &str) -> Option<Self> {
+        let mut lines = data.lines();
+        if lines.next()? != SAVE_HEADER {
+            return None;
+        }
+
+        let order: usize = lines.next()?.trim().parse().ok()?;
+        if !ORDERS.contains(&order) {
+            return None;
         }
 
-        if let Some(Button::Keyboard(key)) = e.press_args() {
-            if key == Key::LShift {
-                self.shift_pressed = true;
+        let mut gameboard = Gameboard::with_order(order);
+        let size = gameboard.size();
+
+        for row in 0..size {
+            let tokens: Vec<&str> = lines.next()?.split_whitespace().collect();
+            if tokens.len() != size {
+                return None;
             }
 
-            if let Some(ind) = self.gameboard.selected_cell {
-                if self.shift_pressed {
-                    match key {
-                        Key::D1 => self.gameboard.note(ind, 1),
-                        Key::D2 => self.gameboard.note(ind, 2),
-                        Key::D3 => self.gameboard.note(ind, 3),
-                        Key::D4 => self.gameboard.note(ind, 4),
-                        Key::D5 => self.gameboard.note(ind, 5),
-                        Key::D6 => self.gameboard.note(ind, 6),
-                        Key::D7 => self.gameboard.note(ind, 7),
-                        Key::D8 => self.gameboard.note(ind, 8),
-                        Key::D9 => self.gameboard.note(ind, 9),
-                        Key::Escape => self.gameboard.set(ind, 0),
+            for (column, token) in tokens.iter().enumerate() {
+                let cell = &mut gameboard.cells[row][column];
+                let mut chars = token.chars().peekable();
+
+                let head = chars.next()?;
+                if head != '.' {
+                    cell.digit = char_digit(head).filter(|&d| d as usize <= size)?;
+                    match chars.peek() {
+                        Some('!') => cell.origin = Origin::Given,
+                        Some('?') => cell.origin = Origin::Hint,
                         _ => ()
                     }
-                } else {
-                    match key {
-                        Key::D1 => self.gameboard.set(ind, 1),
-                        Key::D2 => self.gameboard.set(ind, 2),
-                        Key::D3 => self.gameboard.set(ind, 3),
-                        Key::D4 => self.gameboard.set(ind, 4),
-                        Key::D5 => self.gameboard.set(ind, 5),
-                        Key::D6 => self.gameboard.set(ind, 6),
-                        Key::D7 => self.gameboard.set(ind, 7),
-                        Key::D8 => self.gameboard.set(ind, 8),
-                        Key::D9 => self.gameboard.set(ind, 9),
-                        Key::Escape => self.gameboard.set(ind, 0),
-                        _ => ()
+                    if cell.origin != Origin::Player {
+                        chars.next();
                     }
                 }
+                if chars.peek() == Some(&'#') {
+                    cell.notes_locked = true;
+                    chars.next();
+                }
+
+                match chars.next() {
+                    Some(':') => for ch in chars {
+                        let digit = char_digit(ch).filter(|&d| d as usize <= size)?;
+                        cell.notes[(digit - 1) as usize] = true;
+                    },
+                    None => (),
+                    _ => return None
+                }
             }
         }
 
-        if let Some(Button::Keyboard(key)) = e.release_args() {
-            if key == Key::LShift {
-                self.shift_pressed = false;
+        Some(gameboard)
+    }
+}
+
+// Reads puzzle written as one character per cell, row by row, with '.' or '0'
+// marking empty cells. Whitespace is ignored and board size is inferred from
+// number of cells. All digits become givens.
+impl FromStr for Gameboard {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().filter(|ch| !ch.is_whitespace()).collect();
+        let order = ORDERS.iter().copied()
+            .find(|order| order.pow(4) == chars.len())
+            .ok_or_else(|| format!("Puzzle has {} cells, which isn't a supported board size", chars.len()))?;
+
+        let mut gameboard = Gameboard::with_order(order);
+        let size = gameboard.size();
+
+        for (i, &ch) in chars.iter().enumerate() {
+            let cell = &mut gameboard.cells[i / size][i % size];
+            if ch != '.' && ch != '0' {
+                cell.digit = char_digit(ch).filter(|&d| d as usize <= size)
+                    .ok_or_else(|| format!("Invalid digit '{}' in puzzle", ch))?;
+                cell.origin = Origin::Given;
             }
         }
+
+        Ok(gameboard)
+    }
+}
+
+// Writes digits in the format read by `from_str`, as a single line.
+impl fmt::Display for Gameboard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for cell in self.cells.iter().flatten() {
+            let ch = if cell.digit == 0 { '.' } else { digit_char(cell.digit) };
+            write!(f, "{}", ch)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    const SOLVED: &str = "
+        534678912
+        672195348
+        198342567
+        859761423
+        426853791
+        713924856
+        961537284
+        287419635
+        345286179";
+
+    #[test]
+    fn solved_board_is_complete() {
+        let gameboard: Gameboard = SOLVED.parse().unwrap();
+
+        assert!(gameboard.is_valid());
+        assert!(gameboard.is_complete());
+        assert!(gameboard.find_conflicts().is_empty());
+    }
+
+    #[test]
+    fn row_duplicate_is_caught() {
+        // Swap two digits between rows, so first row holds 3 twice.
+        let gameboard: Gameboard = SOLVED.replacen("534678912", "534678312", 1)
+            .replacen("672195348", "672195948", 1)
+            .parse().unwrap();
+
+        assert!(!gameboard.is_valid());
+        assert!(!gameboard.is_complete());
+        assert!(gameboard.find_conflicts().contains(&[1, 0]));
+        assert!(gameboard.find_conflicts().contains(&[6, 0]));
+    }
+
+    #[test]
+    fn column_duplicate_is_caught() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([4, 0], 7);
+        gameboard.set([4, 8], 7);
+
+        assert!(!gameboard.is_valid());
+        assert_eq!(gameboard.find_conflicts(), vec![[4, 0], [4, 8]]);
+    }
+
+    #[test]
+    fn box_duplicate_is_caught() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([3, 3], 5);
+        gameboard.set([5, 5], 5);
+
+        assert!(!gameboard.is_valid());
+        assert_eq!(gameboard.find_conflicts(), vec![[3, 3], [5, 5]]);
+    }
+
+    #[test]
+    fn incomplete_legal_board_is_valid_but_not_complete() {
+        let gameboard: Gameboard = SOLVED.replacen('5', ".", 3).parse().unwrap();
+
+        assert!(gameboard.is_valid());
+        assert!(!gameboard.is_complete());
+    }
+
+    #[test]
+    fn empty_board_is_valid_but_not_complete() {
+        for &order in ORDERS.iter() {
+            let gameboard = Gameboard::with_order(order);
+
+            assert!(gameboard.is_valid());
+            assert!(!gameboard.is_complete());
+            assert!(gameboard.find_conflicts().is_empty());
+        }
+    }
+
+    #[test]
+    fn from_str_rejects_bad_input() {
+        assert!("123".parse::<Gameboard>().is_err());
+        assert!(SOLVED.replacen('5', "x", 1).parse::<Gameboard>().is_err());
+        // Letters are digits only on 16x16 board.
+        assert!("A...............".parse::<Gameboard>().is_err());
+    }
+
+    #[test]
+    fn locked_notes_block_digits_until_unlocked() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.note([2, 2], 4);
+        gameboard.toggle_notes_lock([2, 2]);
+
+        gameboard.set([2, 2], 4);
+        assert_eq!(gameboard.get_digit([2, 2]), None);
+        assert!(gameboard.get_notes([2, 2])[3]);
+
+        let restored = Gameboard::deserialize(&gameboard.serialize()).unwrap();
+        assert!(restored.is_notes_locked([2, 2]));
+
+        gameboard.toggle_notes_lock([2, 2]);
+        gameboard.set([2, 2], 4);
+        assert_eq!(gameboard.get_digit([2, 2]), Some(4));
+    }
+
+    #[test]
+    fn difficulty_follows_hardest_technique() {
+        let easy: Gameboard = PUZZLE.parse().unwrap();
+        assert_eq!(easy.difficulty(), Difficulty::Easy);
+
+        let evil: Gameboard = "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3.."
+            .parse().unwrap();
+        assert_eq!(evil.difficulty(), Difficulty::Evil);
+    }
+
+    #[test]
+    fn solutions_are_counted_from_givens() {
+        let puzzle: Gameboard = PUZZLE.parse().unwrap();
+        assert_eq!(puzzle.count_solutions(2, UNIQUENESS_NODES), Some(1));
+        assert_eq!(puzzle.solve().unwrap().to_string(), SOLVED.split_whitespace().collect::<String>());
+
+        let repeated: Gameboard = format!("55{}", ".".repeat(79)).parse().unwrap();
+        assert_eq!(repeated.count_solutions(2, UNIQUENESS_NODES), Some(0));
+        assert!(repeated.solve().is_none());
+
+        let empty: Gameboard = ".".repeat(81).parse().unwrap();
+        assert_eq!(empty.count_solutions(2, UNIQUENESS_NODES), Some(2));
+    }
+
+    #[test]
+    fn out_of_range_input_is_ignored() {
+        let mut gameboard = Gameboard::with_order(3);
+
+        gameboard.note([0, 0], 0);
+        gameboard.note([0, 0], 10);
+        gameboard.note([9, 0], 1);
+        gameboard.set([0, 0], 10);
+        gameboard.set([0, 9], 1);
+        gameboard.toggle_notes_lock([9, 9]);
+
+        assert!(gameboard.get_notes([0, 0]).iter().all(|&noted| !noted));
+        assert_eq!(gameboard.get_digit([0, 0]), None);
+        assert_eq!(gameboard.get_digit([9, 0]), None);
+        assert!(gameboard.get_notes([0, 9]).is_empty());
+        assert!(!gameboard.is_given([9, 9]) && !gameboard.is_notes_locked([9, 9]));
+    }
+
+    #[test]
+    fn boundary_digits_are_accepted() {
+        let mut gameboard = Gameboard::with_order(3);
+
+        gameboard.note([8, 8], 1);
+        gameboard.note([8, 8], 9);
+        assert_eq!(gameboard.get_notes([8, 8]).len(), 9);
+        assert!(gameboard.get_notes([8, 8])[0] && gameboard.get_notes([8, 8])[8]);
+
+        gameboard.set([8, 8], 9);
+        assert_eq!(gameboard.get_digit([8, 8]), Some(9));
+        gameboard.set([8, 8], 0);
+        assert_eq!(gameboard.get_digit([8, 8]), None);
+
+        let mut large = Gameboard::with_order(4);
+        large.set([15, 15], 16);
+        assert_eq!(large.get_digit([15, 15]), Some(16));
+    }
+
+    #[test]
+    fn naked_singles_are_filled_from_solution() {
+        let puzzle: Gameboard = PUZZLE.parse().unwrap();
+        let solution = puzzle.solution().unwrap();
+
+        let mut gameboard = puzzle.clone();
+        let filled = gameboard.solve_naked_singles();
+        assert!(filled > 0);
+        assert_eq!(gameboard.empty_cells(), puzzle.empty_cells() - filled);
+        for j in 0..9 {
+            for i in 0..9 {
+                if let Some(digit) = gameboard.get_digit([i, j]) {
+                    assert_eq!(solution.get_digit([i, j]), Some(digit));
+                }
+            }
+        }
+        assert_eq!(gameboard.solve_naked_singles(), 0);
+
+        let mut conflicting = puzzle;
+        conflicting.set([2, 0], 5);
+        assert_eq!(conflicting.solve_naked_singles(), 0);
+    }
+
+    #[test]
+    fn origin_of_digits_is_kept() {
+        let mut gameboard: Gameboard = format!("5{}", ".".repeat(80)).parse().unwrap();
+        gameboard.set([1, 0], 3);
+        gameboard.set_hint([2, 0], 4);
+
+        let restored = Gameboard::deserialize(&gameboard.serialize()).unwrap();
+        assert_eq!(restored.origin([0, 0]), Some(Origin::Given));
+        assert_eq!(restored.origin([1, 0]), Some(Origin::Player));
+        assert_eq!(restored.origin([2, 0]), Some(Origin::Hint));
+        assert_eq!(restored.origin([3, 0]), None);
+
+        gameboard.set([2, 0], 6);
+        assert_eq!(gameboard.origin([2, 0]), Some(Origin::Player));
+    }
+
+    #[test]
+    fn corrupt_save_is_rejected() {
+        let mut gameboard: Gameboard = format!("5{}", ".".repeat(80)).parse().unwrap();
+        gameboard.note([1, 0], 2);
+        let data = gameboard.serialize();
+        assert!(Gameboard::deserialize(&data).is_some());
+
+        // Replaces line of valid save with another one.
+        let with_line = |n: usize, line: &str| {
+            let mut lines: Vec<&str> = data.lines().collect();
+            lines[n] = line;
+            lines.join("\n")
+        };
+        let first_row = data.lines().nth(2).unwrap();
+        let short_row = first_row.rsplit_once(' ').unwrap().0;
+
+        let corrupt = [
+            with_line(0, "sudoku-rs 0"),
+            with_line(1, "5"),
+            with_line(1, "three"),
+            with_line(2, short_row),
+            with_line(2, &first_row.replacen("5!", "X!", 1)),
+            with_line(2, &first_row.replacen("5!", "5!x", 1)),
+            with_line(2, &first_row.replacen(":2", ":0", 1)),
+            data.lines().take(5).collect::<Vec<_>>().join("\n"),
+            String::new()
+        ];
+        for data in &corrupt {
+            assert!(Gameboard::deserialize(data).is_none(), "accepted {:?}", data);
+        }
+    }
+
+    #[test]
+    fn turning_auto_candidates_on_fills_empty_cells() {
+        let mut gameboard: Gameboard = format!("5{}", ".".repeat(80)).parse().unwrap();
+        gameboard.set_auto_candidates(true);
+
+        for ind in (0..81).map(|i| [i % 9, i / 9]).filter(|&ind| ind != [0, 0]) {
+            assert_eq!(gameboard.get_notes(ind), &gameboard.compute_candidates(ind)[..9]);
+        }
+        assert!(!gameboard.get_notes([8, 0])[4]);
+        assert!(gameboard.get_notes([8, 8])[4]);
+        assert!(!gameboard.get_notes([0, 0]).contains(&true));
+    }
+
+    #[test]
+    fn auto_candidates_follow_digits() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([0, 8], 7);
+        gameboard.set_auto_candidates(true);
+
+        // Placing digit removes it from peers only.
+        gameboard.set([0, 0], 7);
+        assert!(!gameboard.get_notes([8, 0])[6]);
+        assert!(!gameboard.get_notes([2, 2])[6]);
+        assert!(gameboard.get_notes([8, 4])[6]);
+
+        // Erasing puts it back where no other 7 blocks it, and emptied cell
+        // gets its own candidates.
+        gameboard.set([0, 0], 0);
+        assert!(gameboard.get_notes([8, 0])[6]);
+        assert!(!gameboard.get_notes([0, 4])[6]);
+        assert_eq!(gameboard.get_notes([0, 0]), &gameboard.compute_candidates([0, 0])[..9]);
+        assert!(!gameboard.get_notes([0, 0])[6]);
+    }
+
+    #[test]
+    fn auto_candidates_skip_locked_notes() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.note([4, 0], 1);
+        gameboard.toggle_notes_lock([4, 0]);
+        gameboard.set_auto_candidates(true);
+        assert_eq!(gameboard.get_notes([4, 0])[..3], [true, false, false]);
+
+        gameboard.set([0, 0], 1);
+        assert!(gameboard.get_notes([4, 0])[0]);
+        gameboard.set([0, 0], 0);
+        assert_eq!(gameboard.get_notes([4, 0])[..3], [true, false, false]);
+    }
+
+    #[test]
+    fn completed_units_are_found() {
+        let solved: Gameboard = SOLVED.parse().unwrap();
+        assert_eq!(solved.completed_units([4, 4]).len(), 3);
+
+        let mut partial = Gameboard::with_order(3);
+        for k in 0..8 {
+            partial.set([k, 0], k as u8 + 1);
+        }
+        assert!(partial.completed_units([8, 0]).is_empty());
+        partial.set([8, 0], 9);
+        assert_eq!(partial.completed_units([8, 0]), vec![(0..9).map(|k| [k, 0]).collect::<Vec<_>>()]);
+
+        // Full unit with repeated digit doesn't count.
+        partial.set([8, 0], 1);
+        assert!(partial.completed_units([8, 0]).is_empty());
+        assert!(Gameboard::with_order(2).completed_units([0, 0]).is_empty());
+    }
+
+    #[test]
+    fn generated_puzzles_have_unique_solution() {
+        // Fewer clues make harder puzzles, so cover the range used by the game and below.
+        for &(order, clues, seed) in &[(2, 4, 1), (2, 6, 2), (3, 40, 3), (3, 30, 4), (3, 24, 5), (3, 17, 6),
+            (4, 96, 7)] {
+
+            let puzzle = Gameboard::generate_seeded_with_order(order, clues, seed);
+            let givens = puzzle.size().pow(2) - puzzle.empty_cells();
+            assert!(givens >= clues, "seed {}", seed);
+            assert_eq!(puzzle.count_solutions(2, 1_000_000), Some(1), "seed {}", seed);
+
+            let solved = puzzle.solve().unwrap();
+            assert!(solved.is_complete(), "seed {}", seed);
+            assert_eq!(solved.puzzle().to_string(), puzzle.to_string());
+        }
+    }
+
+    #[test]
+    fn same_seed_generates_same_puzzle() {
+        let first = Gameboard::generate_seeded(30, 42);
+        assert_eq!(first.to_string(), Gameboard::generate_seeded(30, 42).to_string());
+        assert_ne!(first.to_string(), Gameboard::generate_seeded(30, 43).to_string());
+    }
+
+    #[test]
+    fn logic_steps_lead_to_solution() {
+        let puzzle: Gameboard = PUZZLE.parse().unwrap();
+        let steps = logic::solve(&puzzle).steps;
+        assert_eq!(steps.len(), puzzle.empty_cells());
+
+        let mut gameboard = puzzle;
+        for step in steps {
+            assert_eq!(gameboard.get_digit(step.ind), None);
+            gameboard.set_hint(step.ind, step.digit);
+        }
+        assert_eq!(gameboard.to_string(), SOLVED.split_whitespace().collect::<String>());
+    }
+
+    #[test]
+    fn noting_moves_digit_into_notes() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([0, 0], 5);
+        gameboard.note([0, 0], 3);
+        assert_eq!(gameboard.get_digit([0, 0]), None);
+        assert!(gameboard.get_notes([0, 0])[2] && gameboard.get_notes([0, 0])[4]);
+
+        gameboard.set([1, 0], 7);
+        gameboard.note([1, 0], 7);
+        assert_eq!(gameboard.get_digit([1, 0]), None);
+        assert_eq!(gameboard.get_notes([1, 0]).iter().filter(|&&noted| noted).count(), 1);
+        assert!(gameboard.get_notes([1, 0])[6]);
+
+        // Digit kept above locked notes isn't touched.
+        gameboard.set([2, 0], 1);
+        gameboard.toggle_notes_lock([2, 0]);
+        gameboard.note([2, 0], 2);
+        assert_eq!(gameboard.get_digit([2, 0]), Some(1));
+        assert!(gameboard.get_notes([2, 0]).iter().all(|&noted| !noted));
+    }
+
+    #[test]
+    fn noting_over_digit_keeps_both_with_auto_candidates() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([8, 0], 2);
+        gameboard.set_auto_candidates(true);
+        gameboard.set([0, 0], 4);
+
+        gameboard.note([0, 0], 1);
+        assert_eq!(gameboard.get_digit([0, 0]), None);
+        let notes = gameboard.get_notes([0, 0]);
+        assert!(notes[0] && notes[3]);
+        assert_eq!(notes, &gameboard.compute_candidates([0, 0])[..9]);
+
+        // Noting empty cell still toggles.
+        gameboard.note([0, 0], 1);
+        assert!(!gameboard.get_notes([0, 0])[0]);
+
+        // Digit that isn't a candidate gets noted too, as player asked for it.
+        gameboard.set([0, 0], 7);
+        assert!(!gameboard.compute_candidates([0, 0])[1]);
+        gameboard.note([0, 0], 2);
+        assert!(gameboard.get_notes([0, 0])[1] && gameboard.get_notes([0, 0])[6]);
+    }
+
+    #[test]
+    fn erasing_resets_notes() {
+        let mut gameboard = Gameboard::with_order(3);
+        gameboard.set([0, 0], 4);
+        gameboard.note([1, 0], 2);
+        gameboard.erase([0, 0]);
+        gameboard.erase([1, 0]);
+        assert_eq!(gameboard.get_digit([0, 0]), None);
+        assert!(!gameboard.get_notes([1, 0]).contains(&true));
+
+        gameboard.set([8, 0], 2);
+        gameboard.set_auto_candidates(true);
+        gameboard.set([0, 0], 4);
+        gameboard.erase([0, 0]);
+        assert_eq!(gameboard.get_digit([0, 0]), None);
+        assert_eq!(gameboard.get_notes([0, 0]), &gameboard.compute_candidates([0, 0])[..9]);
+        assert!(gameboard.get_notes([0, 0])[3] && !gameboard.get_notes([0, 0])[1]);
+
+        // Notes toggled by player in empty cell go back to candidates too.
+        gameboard.note([0, 0], 5);
+        gameboard.erase([0, 0]);
+        assert!(gameboard.get_notes([0, 0])[4]);
+    }
+
+    #[test]
+    fn changes_are_reported() {
+        let before = Gameboard::with_order(2);
+        let mut gameboard = before.clone();
+        gameboard.set([1, 0], 3);
+        gameboard.note([0, 1], 2);
+
+        assert_eq!(gameboard.changes_since(&before), vec![
+            BoardEvent::CellSet { ind: [1, 0], old: None, new: Some(3) },
+            BoardEvent::NoteToggled { ind: [0, 1], digit: 2 }
+        ]);
+        assert!(gameboard.changes_since(&gameboard).is_empty());
+
+        let almost: Gameboard = SOLVED.replacen('5', ".", 1).parse().unwrap();
+        let mut finished = almost.clone();
+        finished.set([0, 0], 5);
+        assert_eq!(finished.changes_since(&almost), vec![
+            BoardEvent::CellSet { ind: [0, 0], old: None, new: Some(5) },
+            BoardEvent::Solved
+        ]);
+    }
+
+    #[test]
+    fn solver_gives_up_after_limit() {
+        let puzzle: Gameboard = PUZZLE.parse().unwrap();
+        assert!(puzzle.solve_with_limit(1000).is_ok());
+
+        // Filling empty board takes a placement per cell.
+        let empty = Gameboard::with_order(3);
+        assert_eq!(empty.solve_with_limit(80).err(), Some(SolveError::GaveUp));
+        assert!(empty.solve_with_limit(81).is_ok());
+    }
+
+    #[test]
+    fn check_reports_progress() {
+        let solved: Gameboard = SOLVED.parse().unwrap();
+        assert!(solved.check().is_solved());
+
+        let mut puzzle = solved.clone();
+        puzzle.cells[0][0].origin = Origin::Player;
+        puzzle.cells[0][1].origin = Origin::Player;
+        puzzle.set([1, 0], 0);
+        puzzle.set([0, 0], 4);
+
+        let report = puzzle.check();
+        assert_eq!((report.filled, report.empty), (80, 1));
+        assert!(!report.complete && !report.is_solved());
+        assert!(report.conflicts.contains(&[0, 0]) && report.conflicts.contains(&[2, 0]));
     }
 }
@@ -0,0 +1,8 @@
+// Sudoku engine: board model, parsing and saving, solving, generation and
+// difficulty rating. Doesn't depend on any graphics, the game window lives
+// in the binary.
+pub mod gameboard;
+pub mod logic;
+pub mod save;
+mod rng;
+mod solver;
@@ -0,0 +1,293 @@
+use crate::gameboard::Gameboard;
+use std::fmt;
+
+// Human solving techniques, from simplest to hardest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Technique {
+    // Cell has only one candidate left.
+    NakedSingle,
+    // Digit fits only one cell of a row, column or box.
+    HiddenSingle,
+    // Digit's candidates within a box lie on one line (pointing), or within
+    // a line lie in one box (claiming), ruling it out from rest of the other unit.
+    LockedCandidates,
+    // Two cells of a unit share same two candidates, ruling them out from rest of unit.
+    NakedPair
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::LockedCandidates => "locked candidates",
+            Technique::NakedPair => "naked pair"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// How hard a puzzle is, based on hardest technique needed to solve it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    // Singles are enough.
+    Easy,
+    // Needs locked candidates.
+    Medium,
+    // Needs naked pairs.
+    Hard,
+    // Can't be solved without guessing.
+    Evil
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Evil => "Evil"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+// Single digit placed while solving.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    // Cell coordinates, column first.
+    pub ind: [usize; 2],
+    pub digit: u8,
+    // Hardest technique applied since previous placement.
+    pub technique: Technique
+}
+
+// Outcome of solving board with human techniques.
+pub struct LogicSolution {
+    // Hardest technique used, None if nothing was needed.
+    pub hardest: Option<Technique>,
+    // Whether board got filled. If not, solving needs guessing.
+    pub solved: bool,
+    // Placements in order they were found.
+    pub steps: Vec<Step>
+}
+
+impl LogicSolution {
+    pub fn difficulty(&self) -> Difficulty {
+        if !self.solved {
+            return Difficulty::Evil;
+        }
+
+        match self.hardest {
+            None | Some(Technique::NakedSingle) | Some(Technique::HiddenSingle) => Difficulty::Easy,
+            Some(Technique::LockedCandidates) => Difficulty::Medium,
+            Some(Technique::NakedPair) => Difficulty::Hard
+        }
+    }
+}
+
+// Solves board starting from digits written in it, applying simplest technique
+// that makes progress until board is filled or no technique helps.
+pub fn solve(gameboard: &Gameboard) -> LogicSolution {
+    let mut state = State::new(gameboard);
+    let mut hardest = None;
+    let mut steps = Vec::new();
+    // Hardest technique applied since last placement.
+    let mut pending = None;
+
+    loop {
+        if state.digits.iter().all(|&digit| digit != 0) {
+            return LogicSolution { hardest, solved: true, steps };
+        }
+
+        // Cell without candidates means digits written so far contradict each other.
+        if state.digits.iter().zip(&state.candidates).any(|(&digit, &mask)| digit == 0 && mask == 0) {
+            break;
+        }
+
+        let placement = state.naked_single().map(|found| (found, Technique::NakedSingle))
+            .or_else(|| state.hidden_single().map(|found| (found, Technique::HiddenSingle)));
+
+        let technique = if let Some(((i, digit), technique)) = placement {
+            state.place(i, digit);
+            steps.push(Step {
+                ind: [i % state.size, i / state.size],
+                digit,
+                technique: pending.take().map_or(technique, |pending: Technique| pending.max(technique))
+            });
+            technique
+        } else if state.locked_candidates() {
+            Technique::LockedCandidates
+        } else if state.naked_pairs() {
+            Technique::NakedPair
+        } else {
+            break;
+        };
+
+        hardest = hardest.max(Some(technique));
+        if technique > Technique::HiddenSingle {
+            pending = pending.max(Some(technique));
+        }
+    }
+
+    LogicSolution { hardest, solved: false, steps }
+}
+
+// Board with candidate masks, where bit d - 1 is set if digit d can go in cell.
+struct State {
+    order: usize,
+    size: usize,
+    // Row-major digits, 0 means empty cell.
+    digits: Vec<u8>,
+    candidates: Vec<u32>,
+    // Cells of every row, then every column, then every box.
+    units: Vec<Vec<usize>>
+}
+
+impl State {
+    fn new(gameboard: &Gameboard) -> Self {
+        let order = gameboard.order();
+        let size = gameboard.size();
+
+        let mut units = Vec::with_capacity(3 * size);
+        for row in 0..size {
+            units.push((0..size).map(|column| row * size + column).collect());
+        }
+        for column in 0..size {
+            units.push((0..size).map(|row| row * size + column).collect());
+        }
+        for section in 0..size {
+            units.push((0..size).map(|k| {
+                let row = (section / order) * order + k / order;
+                let column = (section % order) * order + k % order;
+                row * size + column
+            }).collect());
+        }
+
+        let mut state = Self {
+            order,
+            size,
+            digits: vec![0; size * size],
+            candidates: vec![(1 << size) - 1; size * size],
+            units
+        };
+
+        for i in 0..size * size {
+            if let Some(digit) = gameboard.get_digit([i % size, i / size]) {
+                state.place(i, digit);
+            }
+        }
+
+        state
+    }
+
+    // Writes digit and removes it from candidates of peers.
+    fn place(&mut self, i: usize, digit: u8) {
+        self.digits[i] = digit;
+        self.candidates[i] = 0;
+
+        for unit in self.units_of(i) {
+            for k in 0..self.size {
+                let peer = self.units[unit][k];
+                self.candidates[peer] &= !bit(digit);
+            }
+        }
+    }
+
+    // Returns row, column and box units containing cell.
+    fn units_of(&self, i: usize) -> [usize; 3] {
+        let (row, column) = (i / self.size, i % self.size);
+        let section = (row / self.order) * self.order + column / self.order;
+
+        [row, self.size + column, 2 * self.size + section]
+    }
+
+    fn naked_single(&self) -> Option<(usize, u8)> {
+        (0..self.digits.len())
+            .find(|&i| self.digits[i] == 0 && self.candidates[i].count_ones() == 1)
+            .map(|i| (i, self.candidates[i].trailing_zeros() as u8 + 1))
+    }
+
+    fn hidden_single(&self) -> Option<(usize, u8)> {
+        for unit in &self.units {
+            for digit in 1..=self.size as u8 {
+                let mut positions = unit.iter().filter(|&&i| self.candidates[i] & bit(digit) != 0);
+                if let (Some(&i), None) = (positions.next(), positions.next()) {
+                    return Some((i, digit));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Applies pointing and claiming. Returns whether any candidate was removed.
+    fn locked_candidates(&mut self) -> bool {
+        let size = self.size;
+        let mut changed = false;
+
+        for unit in 0..3 * size {
+            for digit in 1..=size as u8 {
+                let positions: Vec<usize> = self.units[unit].iter().copied()
+                    .filter(|&i| self.candidates[i] & bit(digit) != 0)
+                    .collect();
+                if positions.len() < 2 {
+                    continue;
+                }
+
+                // Every other unit containing all positions loses digit elsewhere.
+                let shared: Vec<usize> = self.units_of(positions[0]).iter().copied()
+                    .filter(|&other| other != unit)
+                    .filter(|&other| positions.iter().all(|&i| self.units_of(i).contains(&other)))
+                    .collect();
+
+                for other in shared {
+                    for k in 0..size {
+                        let i = self.units[other][k];
+                        if !positions.contains(&i) && self.candidates[i] & bit(digit) != 0 {
+                            self.candidates[i] &= !bit(digit);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+
+    // Returns whether any candidate was removed.
+    fn naked_pairs(&mut self) -> bool {
+        let mut changed = false;
+
+        for unit in 0..self.units.len() {
+            let cells = self.units[unit].clone();
+            for (a, &first) in cells.iter().enumerate() {
+                let pair = self.candidates[first];
+                if pair.count_ones() != 2 {
+                    continue;
+                }
+
+                let second = match cells[a + 1..].iter().find(|&&i| self.candidates[i] == pair) {
+                    Some(&second) => second,
+                    None => continue
+                };
+
+                for &i in &cells {
+                    if i != first && i != second && self.candidates[i] & pair != 0 {
+                        self.candidates[i] &= !pair;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+fn bit(digit: u8) -> u32 {
+    1 << (digit - 1)
+}
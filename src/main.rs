@@ -1,33 +1,218 @@
-mod gameboard;
-mod button;
+mod batch;
+mod config;
+mod ui;
 
-use gameboard::{Gameboard, GameboardController, GameboardView, GameboardViewSettigs};
-use button::{Button, ButtonController, ButtonView, ButtonViewSettings};
+use config::AppConfig;
+use sudoku_rs::gameboard::{BoardEvent, CheckReport, Gameboard};
+use sudoku_rs::save;
+use ui::board::{GameboardController, GameboardView};
+use ui::button::{Button, ButtonController, ButtonView};
+use ui::help::{self, Action, HelpView, HelpViewSettings};
+use ui::screenshot;
+use ui::status::{StatusView, StatusViewSettings};
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{OpenGL, Filter, GlGraphics, GlyphCache, TextureSettings};
 use piston::event_loop::{EventSettings, Events, EventLoop};
-use piston::input::RenderEvent;
+use piston::input::{Button as InputButton, PressEvent, RenderEvent};
 use piston::window::WindowSettings;
+use std::io;
+use std::path::Path;
+
+// Font compiled into the binary, so the game runs from any directory.
+const FONT: &[u8] = include_bytes!("../assets/UbuntuMono.ttf");
+
+// Returns short seed derived from current time, easy to share with others.
+fn random_seed() -> u64 {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+
+    (now.as_nanos() % 1_000_000) as u64
+}
+
+// Starts new puzzle of given order, with about three eighths of cells given.
+fn new_game(gameboard_controller: &mut GameboardController, order: usize, seed: u64) {
+    let clues = order.pow(4) * 3 / 8;
+    let gameboard = Gameboard::generate_seeded_with_order(order, clues, seed);
+
+    let difficulty = gameboard.difficulty();
+    gameboard_controller.set_gameboard(gameboard);
+    gameboard_controller.set_status(format!("Puzzle #{} ({})", seed, difficulty));
+}
+
+// Describes outcome of check in status line.
+fn check_status(report: &CheckReport) -> String {
+    match report.conflicts.len() {
+        0 if report.is_solved() => "Solved!".to_string(),
+        0 => format!("No conflicts, {} cells left", report.empty),
+        1 => "1 conflict found".to_string(),
+        count => format!("{} conflicts found", count)
+    }
+}
+
+// Saves picture of board to the current directory and reports where it went.
+fn take_screenshot(gameboard_controller: &mut GameboardController, font: &[u8]) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    let path = format!("sudoku-{}.png", now.as_secs());
+
+    let status = match screenshot::export_png(gameboard_controller, font, path.as_ref()) {
+        Ok(()) => format!("Saved screenshot to {}", path),
+        Err(err) => format!("Couldn't save screenshot: {}", err)
+    };
+    gameboard_controller.set_status(status);
+}
+
+// Starts puzzle read from file, using solution from the file if it has one.
+fn load_puzzle(gameboard_controller: &mut GameboardController, path: &Path) -> io::Result<()> {
+    let (gameboard, solution) = save::load_puzzle(path)?;
+
+    let difficulty = gameboard.difficulty();
+    gameboard_controller.set_gameboard(gameboard);
+    if let Some(solution) = solution {
+        gameboard_controller.set_solution(solution);
+    }
+    gameboard_controller.set_status(format!("Loaded {} ({})", path.display(), difficulty));
+    gameboard_controller.warn_if_unsolvable();
+    Ok(())
+}
+
+// Returns board restored from autosave, unless it's missing, corrupt or of other size.
+fn restore_autosave(order: Option<usize>) -> Option<Gameboard> {
+    let path = save::autosave_path()?;
+    if !path.exists() {
+        return None;
+    }
+
+    match save::load(&path) {
+        Ok(gameboard) if order.is_none_or(|order| order == gameboard.order()) => Some(gameboard),
+        Ok(_) => None,
+        Err(err) => {
+            eprintln!("Couldn't restore autosave, starting fresh: {}", err);
+            None
+        }
+    }
+}
+
+// Removes autosave, so next launch starts with fresh board.
+fn clear_autosave() {
+    if let Some(path) = save::autosave_path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let config = match AppConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if let Some(path) = &config.solve_all {
+        match batch::solve_all(path) {
+            Ok(all_unique) => std::process::exit(if all_unique { 0 } else { 2 }),
+            Err(err) => {
+                eprintln!("Couldn't read puzzles {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+    }
+    if config.new_game {
+        clear_autosave();
+    }
+
     let opengl = OpenGL::V4_5;
 
-    let mut window: Window = WindowSettings::new("Sudoku", [512 + 128, 512])
+    let mut window: Window = WindowSettings::new("Sudoku", [config.window_width, config.window_height])
         .graphics_api(opengl)
         .resizable(false)
         .build()
         .expect("Couldn't create window!");
 
+    // Prefer font from config, but fall back to the embedded one.
+    let custom_font = config.font_path.as_ref().and_then(|path| match std::fs::read(path) {
+        Ok(font) => Some(font),
+        Err(err) => {
+            eprintln!("Couldn't read font {}, using embedded one: {}", path.display(), err);
+            None
+        }
+    });
     let texture_settings = TextureSettings::new().filter(Filter::Nearest);
-    let ref mut glyphs = GlyphCache::new("assets/UbuntuMono.ttf", (), texture_settings)
-        .expect("Couldn't load font!");
+    let custom_glyphs = custom_font.as_deref().and_then(|font| {
+        GlyphCache::from_bytes(font, (), texture_settings).ok().map(|glyphs| (font, glyphs))
+    });
+    let (font, mut glyphs) = match custom_glyphs {
+        Some(loaded) => loaded,
+        None => match GlyphCache::from_bytes(FONT, (), texture_settings) {
+            Ok(glyphs) => (FONT, glyphs),
+            Err(_) => {
+                eprintln!("Couldn't load font!");
+                std::process::exit(1);
+            }
+        }
+    };
+    let glyphs = &mut glyphs;
+
+    let order = config.order;
+    let gameboard_view = GameboardView::new(config.gameboard_view_settings());
+    let mut gameboard_controller = GameboardController::new(
+        Gameboard::with_order(order.unwrap_or(3)), gameboard_view);
+    gameboard_controller.set_overwrite_protect(config.overwrite_protect);
+    let loaded = config.puzzle.as_ref().is_some_and(|path| {
+        match load_puzzle(&mut gameboard_controller, path) {
+            Ok(()) => true,
+            Err(err) => {
+                eprintln!("Couldn't load puzzle {}: {}", path.display(), err);
+                false
+            }
+        }
+    });
+    if !loaded {
+        match restore_autosave(order).filter(|_| config.seed.is_none()) {
+            Some(gameboard) => {
+                let difficulty = gameboard.difficulty();
+                gameboard_controller.set_gameboard(gameboard);
+                gameboard_controller.set_status(format!("Restored saved game ({})", difficulty));
+                gameboard_controller.warn_if_unsolvable();
+            },
+            None => new_game(&mut gameboard_controller, order.unwrap_or(3),
+                config.seed.unwrap_or_else(random_seed))
+        }
+    }
 
-    let gameboard_view = GameboardView::new(GameboardViewSettigs::default());
-    let mut gameboard_controller = GameboardController::new(Gameboard::new(), gameboard_view);
+    let mut status_view = StatusView::new(StatusViewSettings::new(config.status_position()));
+    let mut help_view = HelpView::new(HelpViewSettings::new(config.board_position(), config.board_size));
 
-    let button_view = ButtonView::new(ButtonViewSettings::new([498.0, 241.0], [100.0, 30.0]));
+    let buttons = 8;
+    let button_view = ButtonView::new(config.button_view_settings(0, buttons));
+    let mut new_game_controller = ButtonController::new(Button::new("New Game".into()), button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(1, buttons));
     let mut button_controller = ButtonController::new(Button::new("Check".into()), button_view);
 
+    let button_view = ButtonView::new(config.button_view_settings(2, buttons));
+    let mut clear_notes_controller = ButtonController::new(Button::new("Clear Notes".into()),
+        button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(3, buttons));
+    let mut singles_controller = ButtonController::new(Button::new("Fill Singles".into()),
+        button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(4, buttons));
+    let mut solve_controller = ButtonController::new(Button::new("Solve".into()), button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(5, buttons));
+    let mut step_controller = ButtonController::new(Button::new("Step".into()), button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(6, buttons));
+    let mut erase_controller = ButtonController::new(Button::new("Erase".into()), button_view);
+
+    let button_view = ButtonView::new(config.button_view_settings(7, buttons));
+    let mut screenshot_controller = ButtonController::new(Button::new("Screenshot".into()),
+        button_view);
     let mut gl = GlGraphics::new(opengl);
     let mut events = Events::new(EventSettings::new().lazy(true));
     while let Some(event) = events.next(&mut window) {
@@ -37,13 +222,64 @@ fn main() {
 
                 clear([1.0; 4], g);
                 gameboard_controller.draw(c, g, glyphs);
+                new_game_controller.draw(c, g, glyphs);
                 button_controller.draw(c, g, glyphs);
+                clear_notes_controller.draw(c, g, glyphs);
+                singles_controller.draw(c, g, glyphs);
+                solve_controller.draw(c, g, glyphs);
+                step_controller.draw(c, g, glyphs);
+                erase_controller.draw(c, g, glyphs);
+                screenshot_controller.draw(c, g, glyphs);
+                status_view.draw(gameboard_controller.status(), c, g, glyphs);
+                if gameboard_controller.show_help() {
+                    help_view.draw(c, g, glyphs);
+                }
             });
         }
 
         gameboard_controller.handle_event(&event);
+        if new_game_controller.handle_event(&event) {
+            let order = gameboard_controller.gameboard().order();
+            new_game(&mut gameboard_controller, order, random_seed());
+        }
         if button_controller.handle_event(&event) {
-            println!("Check: {}", gameboard_controller.check());
+            let report = gameboard_controller.check();
+            gameboard_controller.set_status(check_status(&report));
+        }
+        if clear_notes_controller.handle_event(&event) {
+            gameboard_controller.clear_all_notes();
+        }
+        if singles_controller.handle_event(&event) {
+            gameboard_controller.solve_naked_singles();
+        }
+        if solve_controller.handle_event(&event) {
+            gameboard_controller.solve();
+        }
+        if step_controller.handle_event(&event) {
+            gameboard_controller.step();
+        }
+        if erase_controller.handle_event(&event) {
+            gameboard_controller.toggle_erase_mode();
+        }
+        erase_controller.set_active(gameboard_controller.erase_mode());
+        if screenshot_controller.handle_event(&event) ||
+            matches!(event.press_args(), Some(InputButton::Keyboard(key))
+                if help::action_for(key) == Some(Action::Screenshot)) {
+            take_screenshot(&mut gameboard_controller, font);
+        }
+
+        if gameboard_controller.drain_events().contains(&BoardEvent::Solved) {
+            gameboard_controller.set_status("Solved! Press New Game to play again".to_string());
+        }
+
+        // Keep updating only while something is animated.
+        events.set_lazy(!gameboard_controller.is_animating());
+    }
+
+    // Window was closed, keep progress for next launch.
+    if let Some(path) = save::autosave_path() {
+        if let Err(err) = save::save(gameboard_controller.gameboard(), &path) {
+            eprintln!("Couldn't autosave board: {}", err);
         }
     }
 }
@@ -0,0 +1,93 @@
+use crate::gameboard::Gameboard;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Writes board to file, creating parent directories if needed.
+pub fn save(gameboard: &Gameboard, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, gameboard.serialize())
+}
+
+// Reads board from file. Fails with `InvalidData` if file is corrupt.
+pub fn load(path: &Path) -> io::Result<Gameboard> {
+    let data = fs::read_to_string(path)?;
+
+    Gameboard::deserialize(&data)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Corrupt save file"))
+}
+
+// Reads puzzle file: puzzle on first line, optionally followed by its intended
+// solution on second one. Empty lines and lines starting with '#' are skipped.
+pub fn load_puzzle(path: &Path) -> io::Result<(Gameboard, Option<Gameboard>)> {
+    let data = fs::read_to_string(path)?;
+
+    parse_puzzle(&data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn parse_puzzle(data: &str) -> Result<(Gameboard, Option<Gameboard>), String> {
+    let mut lines = data.lines().map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let puzzle: Gameboard = lines.next().ok_or("Puzzle file is empty")?.parse()?;
+    let solution: Option<Gameboard> = lines.next().map(str::parse).transpose()?;
+    if lines.next().is_some() {
+        return Err("Puzzle file has more than two lines".into());
+    }
+
+    if let Some(solution) = &solution {
+        let size = puzzle.size();
+        let matches_givens = (0..size * size).map(|i| [i % size, i / size])
+            .all(|ind| puzzle.get_digit(ind).is_none_or(|digit| solution.get_digit(ind) == Some(digit)));
+
+        if solution.order() != puzzle.order() || !solution.is_complete() || !matches_givens {
+            return Err("Solution doesn't solve the puzzle".into());
+        }
+    }
+
+    Ok((puzzle, solution))
+}
+
+// Returns path of file board is autosaved to, if data directory can be found.
+pub fn autosave_path() -> Option<PathBuf> {
+    let data_dir = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+    };
+
+    data_dir.map(|dir| dir.join("sudoku-rs").join("autosave.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    const SOLUTION: &str = "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    #[test]
+    fn solution_line_is_optional() {
+        let (puzzle, solution) = parse_puzzle(PUZZLE).unwrap();
+        assert_eq!(puzzle.to_string(), PUZZLE);
+        assert!(solution.is_none());
+
+        let (_, solution) = parse_puzzle(&format!("# Lesson 1\n{}\n\n{}\n", PUZZLE, SOLUTION)).unwrap();
+        assert_eq!(solution.unwrap().to_string(), SOLUTION);
+    }
+
+    #[test]
+    fn solution_must_fit_puzzle() {
+        let wrong_given = SOLUTION.replacen('5', "4", 1);
+        let unfinished = SOLUTION.replacen('5', ".", 1);
+
+        assert!(parse_puzzle(&format!("{}\n{}", PUZZLE, wrong_given)).is_err());
+        assert!(parse_puzzle(&format!("{}\n{}", PUZZLE, unfinished)).is_err());
+        assert!(parse_puzzle(&format!("{}\n{}\n{}", PUZZLE, SOLUTION, SOLUTION)).is_err());
+        assert!(parse_puzzle("").is_err());
+    }
+}
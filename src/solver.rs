@@ -0,0 +1,232 @@
+use crate::rng::Rng;
+
+// Compact board representation used for searching solutions.
+// Digits used in every row, column and box are kept as bit masks,
+// where bit d - 1 is set if digit d is present.
+pub struct Grid {
+    order: usize,
+    size: usize,
+    // Row-major digits, 0 means empty cell.
+    digits: Vec<u8>,
+    rows: Vec<u32>,
+    columns: Vec<u32>,
+    boxes: Vec<u32>
+}
+
+impl Grid {
+    // Creates empty grid of given order.
+    pub fn new(order: usize) -> Self {
+        let size = order * order;
+        Self {
+            order,
+            size,
+            digits: vec![0; size * size],
+            rows: vec![0; size],
+            columns: vec![0; size],
+            boxes: vec![0; size]
+        }
+    }
+
+    // Returns digit at given row-major index, 0 if empty.
+    pub fn get(&self, i: usize) -> u8 {
+        self.digits[i]
+    }
+
+    // Returns whether digit can be written in cell without repeating it in row,
+    // column or box.
+    pub fn can_place(&self, i: usize, digit: u8) -> bool {
+        self.candidates(i) & Grid::bit(digit) != 0
+    }
+
+    // Writes digit into empty cell.
+    pub fn place(&mut self, i: usize, digit: u8) {
+        let (row, column, section) = self.units(i);
+        let bit = Grid::bit(digit);
+
+        self.digits[i] = digit;
+        self.rows[row] |= bit;
+        self.columns[column] |= bit;
+        self.boxes[section] |= bit;
+    }
+
+    // Erases digit from cell.
+    pub fn clear(&mut self, i: usize) {
+        let (row, column, section) = self.units(i);
+        let bit = !Grid::bit(self.digits[i]);
+
+        self.digits[i] = 0;
+        self.rows[row] &= bit;
+        self.columns[column] &= bit;
+        self.boxes[section] &= bit;
+    }
+
+    // Counts solutions, stopping once limit is reached. Gives up and returns None
+    // after trying max_nodes placements.
+    pub fn count_solutions(&mut self, limit: usize, max_nodes: usize) -> Option<usize> {
+        let mut found = 0;
+        let mut nodes = max_nodes;
+        if self.count(limit, &mut found, &mut nodes) {
+            Some(found)
+        } else {
+            None
+        }
+    }
+
+    // Fills all empty cells with first solution found. When rng is given, choices are
+    // tried in random order. Returns false, leaving grid untouched, if there is no solution.
+    // Gives up, returning None with grid untouched, after trying max_nodes placements.
+    pub fn fill(&mut self, rng: Option<&mut Rng>, max_nodes: usize) -> Option<bool> {
+        let mut nodes = max_nodes;
+        self.fill_nodes(rng, &mut nodes)
+    }
+
+    fn fill_nodes(&mut self, mut rng: Option<&mut Rng>, nodes: &mut usize) -> Option<bool> {
+        let mut choices = match self.branch() {
+            Some(choices) => choices,
+            None => return Some(true)
+        };
+        if let Some(rng) = rng.as_deref_mut() {
+            rng.shuffle(&mut choices);
+        }
+
+        for (i, digit) in choices {
+            if *nodes == 0 {
+                return None;
+            }
+            *nodes -= 1;
+
+            self.place(i, digit);
+            let filled = self.fill_nodes(rng.as_deref_mut(), nodes);
+            if filled != Some(false) {
+                if filled.is_none() {
+                    self.clear(i);
+                }
+                return filled;
+            }
+            self.clear(i);
+        }
+
+        Some(false)
+    }
+
+    // Returns false if node budget ran out.
+    fn count(&mut self, limit: usize, found: &mut usize, nodes: &mut usize) -> bool {
+        let choices = match self.branch() {
+            Some(choices) => choices,
+            None => {
+                *found += 1;
+                return true;
+            }
+        };
+
+        for (i, digit) in choices {
+            if *nodes == 0 {
+                return false;
+            }
+            *nodes -= 1;
+
+            self.place(i, digit);
+            let finished = self.count(limit, found, nodes);
+            self.clear(i);
+
+            if !finished {
+                return false;
+            }
+            if *found >= limit {
+                break;
+            }
+        }
+
+        true
+    }
+
+    // Returns smallest set of (cell, digit) placements one of which must be true:
+    // either candidates of most constrained cell, or positions where a digit can go
+    // within a row, column or box. Returns None if grid is full, and no placements
+    // at a dead end.
+    fn branch(&self) -> Option<Vec<(usize, u8)>> {
+        let size = self.size;
+        let mut masks = vec![0; self.digits.len()];
+        let mut best: Option<Vec<(usize, u8)>> = None;
+
+        for (i, mask) in masks.iter_mut().enumerate() {
+            if self.digits[i] != 0 {
+                continue;
+            }
+
+            *mask = self.candidates(i);
+            if best.as_ref().is_none_or(|best| (mask.count_ones() as usize) < best.len()) {
+                best = Some((1..=size as u8)
+                    .filter(|&digit| *mask & Grid::bit(digit) != 0)
+                    .map(|digit| (i, digit))
+                    .collect());
+            }
+        }
+
+        let mut best = best?;
+        if best.len() <= 1 {
+            return Some(best);
+        }
+
+        for unit in 0..3 * size {
+            let cells: Vec<usize> = (0..size).map(|k| self.unit_cell(unit, k)).collect();
+            let used = cells.iter().fold(0, |used, &i| {
+                if self.digits[i] != 0 { used | Grid::bit(self.digits[i]) } else { used }
+            });
+
+            for digit in (1..=size as u8).filter(|&digit| used & Grid::bit(digit) == 0) {
+                let positions: Vec<(usize, u8)> = cells.iter()
+                    .filter(|&&i| masks[i] & Grid::bit(digit) != 0)
+                    .map(|&i| (i, digit))
+                    .collect();
+
+                if positions.len() < best.len() {
+                    best = positions;
+                    if best.len() <= 1 {
+                        return Some(best);
+                    }
+                }
+            }
+        }
+
+        Some(best)
+    }
+
+    // Returns k-th cell of unit. Units 0..size are rows, then columns, then boxes.
+    fn unit_cell(&self, unit: usize, k: usize) -> usize {
+        let size = self.size;
+        let order = self.order;
+
+        match unit / size {
+            0 => unit * size + k,
+            1 => k * size + unit % size,
+            _ => {
+                let section = unit % size;
+                let row = (section / order) * order + k / order;
+                let column = (section % order) * order + k % order;
+                row * size + column
+            }
+        }
+    }
+
+    // Returns mask of digits that can be written in cell.
+    fn candidates(&self, i: usize) -> u32 {
+        let (row, column, section) = self.units(i);
+        let all = (1 << self.size) - 1;
+
+        all & !(self.rows[row] | self.columns[column] | self.boxes[section])
+    }
+
+    // Returns row, column and box of cell.
+    fn units(&self, i: usize) -> (usize, usize, usize) {
+        let row = i / self.size;
+        let column = i % self.size;
+        let section = (row / self.order) * self.order + column / self.order;
+
+        (row, column, section)
+    }
+
+    fn bit(digit: u8) -> u32 {
+        1 << (digit - 1)
+    }
+}
@@ -0,0 +1,1258 @@
+use graphics::{Graphics, character::CharacterCache, Context, types::Color};
+use piston::generic_event::GenericEvent;
+use std::collections::VecDeque;
+use sudoku_rs::gameboard::{digit_char, BoardEvent, CheckReport, Gameboard, Origin, SolveError};
+use sudoku_rs::logic::{self, Step, Technique};
+use crate::ui::help::{self, Action};
+
+// Seconds between digits written while solving whole board.
+const SOLVE_INTERVAL: f64 = 0.03;
+
+// Number of placements solver may try before giving up, so window doesn't freeze.
+const SOLVE_NODES: usize = 200_000;
+
+// How notes are arranged inside cell.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoteLayout {
+    // Every digit has its own place in a mini-grid, like cells of a box.
+    Grid,
+    // Noted digits are packed one after another in a single line.
+    Row
+}
+
+// Stores settings for game board view.
+pub struct GameboardViewSettigs {
+    // Position from top-left corner.
+    pub position: [f64; 2],
+    // Size along horizontal and vertical edge.
+    pub size: f64,
+    // Color of background.
+    pub background_color: Color,
+    // Color of edge around board.
+    pub board_edge_color: Color,
+    // Color of edge around box section.
+    pub section_edge_color: Color,
+    // Color of edge around single cell.
+    pub cell_edge_color: Color,
+    // Backgrond color of selected cell.
+    pub selected_cell_background_color: Color,
+    // Color of border around selected cell.
+    pub selected_cell_border_color: Color,
+    // Radius of border around selected cell.
+    pub selected_cell_border_radius: f64,
+    // Background color of cell under mouse cursor.
+    pub hovered_cell_background_color: Color,
+    // Background color of cells holding same digit as selected cell.
+    pub same_digit_cell_background_color: Color,
+    // Background color of unit just completed by player, fading out.
+    pub completed_unit_background_color: Color,
+    // Time in seconds for completed unit highlight to fade out.
+    pub completed_unit_flash_duration: f64,
+    // Background color of cell changed last, fading out.
+    pub last_modified_background_color: Color,
+    // Time in seconds for last changed cell highlight to fade out.
+    pub last_modified_duration: f64,
+    // Background color of cells found in conflict by last check.
+    pub conflict_cell_background_color: Color,
+    // Color of border around cell with locked notes.
+    pub locked_cell_border_color: Color,
+    // Radius of border around cell with locked notes.
+    pub locked_cell_border_radius: f64,
+    // Radius of edge around board.
+    pub board_edge_radius: f64,
+    // Radius of edge around box section.
+    pub section_edge_radius: f64,
+    // Radius of edge around single cell.
+    pub cell_edge_radius: f64,
+    // Color of font.
+    pub text_color: Color,
+    // Color of font for digits given by the puzzle.
+    pub given_text_color: Color,
+    // Color of font for digits filled by hint or solver.
+    pub hint_text_color: Color,
+    // Color of font for digits differing from puzzle solution.
+    pub wrong_entry_text_color: Color,
+    // Whether digits differing from puzzle solution are highlighted.
+    pub highlight_wrong_entries: bool,
+    // Size of font on 9x9 board. Scaled for other board sizes.
+    pub font_size: u32,
+    // Time in seconds for written digit to fade in.
+    pub digit_animation_duration: f64,
+    // Whether notes are drawn. Hiding them leaves them in the board.
+    pub show_notes: bool,
+    // Arrangement of notes inside cell.
+    pub note_layout: NoteLayout,
+    // Color of font for notes.
+    pub note_color: Color,
+    // Color of font for notes matching digit of selected cell.
+    pub highlighted_note_color: Color,
+    // Size of font for notes on 9x9 board. Scaled for other board sizes.
+    pub note_font_size: u32
+}
+
+impl Default for GameboardViewSettigs {
+    fn default() -> Self {
+        Self {
+            position: [56.0; 2],
+            size: 400.0,
+            background_color: [0.8, 0.8, 1.0, 1.0],
+            board_edge_color: [0.0, 0.0, 0.2, 1.0],
+            section_edge_color: [0.0, 0.0, 0.2, 1.0],
+            cell_edge_color: [0.0, 0.0, 0.2, 1.0],
+            selected_cell_background_color: [0.9, 0.9, 1.0, 1.0],
+            selected_cell_border_color: [0.1, 0.3, 0.9, 1.0],
+            selected_cell_border_radius: 2.0,
+            hovered_cell_background_color: [0.85, 0.85, 1.0, 1.0],
+            same_digit_cell_background_color: [0.7, 0.7, 0.95, 1.0],
+            completed_unit_background_color: [0.6, 0.9, 0.6, 1.0],
+            completed_unit_flash_duration: 0.5,
+            last_modified_background_color: [1.0, 0.9, 0.5, 1.0],
+            last_modified_duration: 1.0,
+            conflict_cell_background_color: [1.0, 0.75, 0.75, 1.0],
+            locked_cell_border_color: [0.9, 0.5, 0.0, 1.0],
+            locked_cell_border_radius: 1.5,
+            board_edge_radius: 3.0,
+            section_edge_radius: 2.0,
+            cell_edge_radius: 1.0,
+            text_color: [0.0, 0.0, 1.0, 1.0],
+            given_text_color: [0.0, 0.0, 0.2, 1.0],
+            hint_text_color: [0.0, 0.5, 0.3, 1.0],
+            wrong_entry_text_color: [0.8, 0.0, 0.0, 1.0],
+            highlight_wrong_entries: true,
+            font_size: 34,
+            digit_animation_duration: 0.15,
+            show_notes: true,
+            note_layout: NoteLayout::Grid,
+            note_color: [0.37, 0.37, 0.63, 1.0],
+            highlighted_note_color: [0.9, 0.4, 0.0, 1.0],
+            note_font_size: 10
+        }
+    }
+}
+
+// Digit fading and growing in after being written.
+struct DigitAnimation {
+    ind: [usize; 2],
+    // Seconds of updates since digit was written. Digit starts hidden, and
+    // controller keeps updates coming while animating.
+    elapsed: f64
+}
+
+pub struct GameboardView {
+    settings: GameboardViewSettigs,
+    animations: Vec<DigitAnimation>
+}
+
+impl GameboardView {
+    pub fn new(settings: GameboardViewSettigs) -> Self {
+        Self {
+            settings,
+            animations: Vec::new()
+        }
+    }
+
+    // Starts animating digit just written in cell.
+    pub fn animate_digit(&mut self, ind: [usize; 2]) {
+        self.animations.retain(|animation| animation.ind != ind);
+        self.animations.push(DigitAnimation { ind, elapsed: 0.0 });
+    }
+
+    pub fn is_animating(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    // Advances animations by dt seconds, dropping finished ones.
+    pub fn update(&mut self, dt: f64) {
+        let duration = self.settings.digit_animation_duration;
+        for animation in &mut self.animations {
+            animation.elapsed += dt;
+        }
+        self.animations.retain(|animation| animation.elapsed < duration);
+    }
+
+    pub fn stop_animations(&mut self) {
+        self.animations.clear();
+    }
+
+    // Returns how far digit in cell got with appearing, from 0 to 1.
+    fn digit_progress(&self, ind: [usize; 2]) -> f64 {
+        self.animations.iter()
+            .find(|animation| animation.ind == ind)
+            .map_or(1.0, |animation| (animation.elapsed / self.settings.digit_animation_duration).min(1.0))
+    }
+
+    pub fn draw<G, C>(&self, controller: &GameboardController, c: &Context, g: &mut G, glyphs: &mut C)
+        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
+        use graphics::*;
+
+        let settings = &self.settings;
+        let gameboard = &controller.gameboard;
+        let order = gameboard.order();
+        let size = gameboard.size();
+        let cell_size = settings.size / size as f64;
+        let font_size = settings.font_size * 9 / size as u32;
+        let note_font_size = settings.note_font_size * 3 / order as u32;
+        let board_rect = [
+            settings.position[0], settings.position[1],
+            settings.size, settings.size
+        ];
+
+        // Draw board background.
+        Rectangle::new(settings.background_color)
+            .draw(board_rect, &c.draw_state, c.transform, g);
+
+        // Draw hovered cell background.
+        if let Some(ind) = controller.hovered_cell {
+            self.draw_cell_background(ind, cell_size, settings.hovered_cell_background_color, c, g);
+        }
+
+        // Draw background of other cells with digit of selected cell.
+        let selected_digit = controller.selected_cell.and_then(|ind| gameboard.get_digit(ind));
+        if let Some(digit) = selected_digit {
+            for j in 0..size {
+                for i in 0..size {
+                    if gameboard.get_digit([i, j]) == Some(digit) {
+                        self.draw_cell_background([i, j], cell_size,
+                            settings.same_digit_cell_background_color, c, g);
+                    }
+                }
+            }
+        }
+
+        // Draw background of cells in conflict.
+        for &ind in &controller.conflicts {
+            self.draw_cell_background(ind, cell_size, settings.conflict_cell_background_color, c, g);
+        }
+
+        // Draw fading background of just completed units.
+        for flash in &controller.flashes {
+            let mut color = settings.completed_unit_background_color;
+            color[3] *= (flash.time_left / settings.completed_unit_flash_duration) as f32;
+            for &ind in &flash.cells {
+                self.draw_cell_background(ind, cell_size, color, c, g);
+            }
+        }
+
+        // Draw selected cell background.
+        if let Some(ind) = controller.selected_cell {
+            self.draw_cell_background(ind, cell_size, settings.selected_cell_background_color, c, g);
+        }
+
+        // Draw fading background of cell changed last, over selection as it's usually
+        // the selected cell.
+        if let Some((ind, time_left)) = controller.last_modified {
+            let mut color = settings.last_modified_background_color;
+            color[3] *= (time_left / settings.last_modified_duration) as f32;
+            self.draw_cell_background(ind, cell_size, color, c, g);
+        }
+
+        // Draw digits.
+        for j in 0..size {
+            for i in 0..size {
+                let pos = [
+                    settings.position[0] + i as f64 * cell_size,
+                    settings.position[1] + j as f64 * cell_size
+                ];
+
+                if let Some(digit) = gameboard.get_digit([i, j]) {
+                    let wrong = settings.highlight_wrong_entries && controller.solution.as_ref()
+                        .is_some_and(|solution| solution.get_digit([i, j]) != Some(digit));
+                    let mut color = match gameboard.origin([i, j]) {
+                        Some(Origin::Given) => settings.given_text_color,
+                        _ if wrong => settings.wrong_entry_text_color,
+                        Some(Origin::Hint) => settings.hint_text_color,
+                        _ => settings.text_color
+                    };
+                    let progress = self.digit_progress([i, j]);
+                    color[3] *= progress as f32;
+                    let text_image = Image::new_color(color);
+                    if let Ok(character) = glyphs.character(font_size,
+                        digit_char(digit)) {
+
+                        let ch_x = pos[0] + (cell_size - character.atlas_size[0]) / 2.0;
+                        let ch_y = pos[1] + (cell_size - character.atlas_size[1]) / 2.0;
+
+                        let text_image = text_image.src_rect([
+                            character.atlas_offset[0],
+                            character.atlas_offset[1],
+                            character.atlas_size[0],
+                            character.atlas_size[1]
+                        ]);
+
+                        // Grow from half size around glyph center.
+                        let scale = 0.5 + 0.5 * progress;
+                        let half = [character.atlas_size[0] / 2.0, character.atlas_size[1] / 2.0];
+                        let transform = c.transform.trans(ch_x + half[0], ch_y + half[1])
+                            .scale(scale, scale)
+                            .trans(-half[0], -half[1]);
+                        text_image.draw(character.texture, &c.draw_state, transform, g);
+                    }
+                } else if settings.show_notes {
+                    let noted: Vec<u8> = gameboard.get_notes([i, j]).iter().enumerate()
+                        .filter(|(_, &noted)| noted)
+                        .map(|(n, _)| (n + 1) as u8)
+                        .collect();
+
+                    // Centers of note glyphs, in order of noted digits.
+                    let centers: Vec<[f64; 2]> = match settings.note_layout {
+                        NoteLayout::Grid => {
+                            let note_size = cell_size / order as f64;
+                            noted.iter().map(|&digit| {
+                                let n = (digit - 1) as usize;
+                                [
+                                    pos[0] + note_size * (0.5 + (n % order) as f64),
+                                    pos[1] + note_size * (0.5 + (n / order) as f64)
+                                ]
+                            }).collect()
+                        },
+                        NoteLayout::Row => {
+                            // Squeeze notes together if they don't fit in the cell.
+                            let advance = glyphs.width(note_font_size, "0").unwrap_or(0.0);
+                            let spacing = advance.min(cell_size * 0.9 / noted.len() as f64);
+                            let left = pos[0] + (cell_size - spacing * noted.len() as f64) / 2.0;
+                            (0..noted.len())
+                                .map(|k| [left + spacing * (k as f64 + 0.5), pos[1] + cell_size / 2.0])
+                                .collect()
+                        }
+                    };
+
+                    for (&digit, center) in noted.iter().zip(centers) {
+                        let text_image = Image::new_color(if selected_digit == Some(digit) {
+                            settings.highlighted_note_color
+                        } else {
+                            settings.note_color
+                        });
+                        if let Ok(character) = glyphs.character(note_font_size, digit_char(digit)) {
+                            let ch_x = center[0] - character.atlas_size[0] / 2.0;
+                            let ch_y = center[1] - character.atlas_size[1] / 2.0;
+
+                            let text_image = text_image.src_rect([
+                                character.atlas_offset[0],
+                                character.atlas_offset[1],
+                                character.atlas_size[0],
+                                character.atlas_size[1]
+                            ]);
+
+                            let transform = c.transform.trans(ch_x, ch_y);
+                            text_image.draw(character.texture, &c.draw_state, transform, g);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Draw grid. Cell lines come first, then section lines and board edge on top,
+        // so heavier lines aren't cut where they cross lighter ones.
+        let edges = [
+            Line::new(settings.cell_edge_color, settings.cell_edge_radius),
+            Line::new(settings.section_edge_color, settings.section_edge_radius),
+            Line::new(settings.board_edge_color, settings.board_edge_radius)
+        ];
+        let rank = |i: usize| if i == 0 || i == size { 2 } else if i.is_multiple_of(order) { 1 } else { 0 };
+
+        for (edge_rank, edge) in edges.iter().enumerate() {
+            for i in (0..=size).filter(|&i| rank(i) == edge_rank) {
+                let x = settings.position[0] + i as f64 / size as f64 * settings.size;
+                let y = settings.position[1] + i as f64 / size as f64 * settings.size;
+                let x2 = settings.position[0] + settings.size;
+                let y2 = settings.position[1] + settings.size;
+
+                let vline = [x, settings.position[1], x, y2];
+                let hline = [settings.position[0], y, x2, y];
+
+                edge.draw(vline, &c.draw_state, c.transform, g);
+                edge.draw(hline, &c.draw_state, c.transform, g);
+            }
+        }
+
+        // Draw border inside cells with locked notes.
+        let lock_border = Rectangle::new_border(settings.locked_cell_border_color,
+            settings.locked_cell_border_radius);
+        for j in 0..size {
+            for i in 0..size {
+                if gameboard.is_notes_locked([i, j]) {
+                    let inset = settings.cell_edge_radius + settings.locked_cell_border_radius;
+                    let cell_rect = [
+                        settings.position[0] + i as f64 * cell_size + inset,
+                        settings.position[1] + j as f64 * cell_size + inset,
+                        cell_size - 2.0 * inset, cell_size - 2.0 * inset
+                    ];
+                    lock_border.draw(cell_rect, &c.draw_state, c.transform, g);
+                }
+            }
+        }
+
+        // Draw focus ring around selected cell, on top of everything else.
+        if let Some(ind) = controller.selected_cell {
+            let inset = settings.cell_edge_radius + settings.selected_cell_border_radius;
+            let cell_rect = [
+                settings.position[0] + ind[0] as f64 * cell_size + inset,
+                settings.position[1] + ind[1] as f64 * cell_size + inset,
+                cell_size - 2.0 * inset, cell_size - 2.0 * inset
+            ];
+            Rectangle::new_border(settings.selected_cell_border_color, settings.selected_cell_border_radius)
+                .draw(cell_rect, &c.draw_state, c.transform, g);
+        }
+    }
+
+    // Fills background of single cell with color.
+    fn draw_cell_background<G>(&self, ind: [usize; 2], cell_size: f64, color: Color,
+        c: &Context, g: &mut G) where G: Graphics {
+        use graphics::*;
+
+        let settings = &self.settings;
+        let cell_rect = [
+            settings.position[0] + ind[0] as f64 * cell_size,
+            settings.position[1] + ind[1] as f64 * cell_size,
+            cell_size, cell_size
+        ];
+
+        Rectangle::new(color)
+            .draw(cell_rect, &c.draw_state, c.transform, g);
+    }
+}
+
+// Highlight of completed unit, fading out as time passes.
+struct Flash {
+    cells: Vec<[usize; 2]>,
+    // Seconds until highlight disappears.
+    time_left: f64
+}
+
+// Digit planned to be written by solver or step sequence.
+struct Placement {
+    ind: [usize; 2],
+    digit: u8,
+    // Technique that found digit, None if it's taken from solution.
+    technique: Option<Technique>
+}
+
+impl From<Step> for Placement {
+    fn from(step: Step) -> Self {
+        Self { ind: step.ind, digit: step.digit, technique: Some(step.technique) }
+    }
+}
+
+pub struct GameboardController {
+    gameboard: Gameboard,
+    // Solution of current puzzle, computed when puzzle starts unless one was provided.
+    solution: Option<Gameboard>,
+    gameboard_view: GameboardView,
+    cursor_pos: [f64; 2],
+    selected_cell: Option<[usize; 2]>,
+    // Cell under mouse cursor, None if cursor is outside of board.
+    hovered_cell: Option<[usize; 2]>,
+    shift_pressed: bool,
+    // Whether digit keys note digits instead of writing them. Holding Shift
+    // does the opposite of current mode.
+    note_mode: bool,
+    // Whether clicking a cell clears its digit and notes instead of selecting it.
+    erase_mode: bool,
+    // Cells in conflict found by last check, highlighted until next edit.
+    conflicts: Vec<[usize; 2]>,
+    // Digits typed so far after pressing J to jump to a cell: row, then column.
+    // None when not jumping.
+    goto_entry: Option<Vec<u8>>,
+    // Units completed recently, highlighted for a moment.
+    flashes: Vec<Flash>,
+    // Cell changed last with seconds until its highlight disappears.
+    last_modified: Option<([usize; 2], f64)>,
+    // Whether solver is writing digits or step sequence is active. Board can't be
+    // edited meanwhile, so it stays consistent with planned steps.
+    busy: bool,
+    // Placements solver is going to write, first one next.
+    planned_steps: VecDeque<Placement>,
+    // Number of steps in current sequence, for showing progress.
+    total_steps: usize,
+    // Whether planned steps are written one by one over time, instead of on request.
+    solving: bool,
+    // Seconds until next planned step is written while solving.
+    solve_timer: f64,
+    // Changes of board not yet taken by `drain_events`.
+    events: Vec<BoardEvent>,
+    // Whether list of shortcuts is shown over the board.
+    show_help: bool,
+    // Whether digit keys refuse to replace another digit, so cell must be erased first.
+    overwrite_protect: bool,
+    // Message shown to the player in status line.
+    status: String
+}
+
+impl GameboardController {
+    pub fn new(gameboard: Gameboard, gameboard_view: GameboardView) -> Self {
+        Self {
+            solution: gameboard.solution(),
+            gameboard,
+            gameboard_view,
+            cursor_pos: [0.0; 2],
+            selected_cell: None,
+            hovered_cell: None,
+            shift_pressed: false,
+            note_mode: false,
+            erase_mode: false,
+            conflicts: Vec::new(),
+            goto_entry: None,
+            flashes: Vec::new(),
+            last_modified: None,
+            busy: false,
+            planned_steps: VecDeque::new(),
+            total_steps: 0,
+            solving: false,
+            solve_timer: 0.0,
+            events: Vec::new(),
+            show_help: false,
+            overwrite_protect: false,
+            status: String::new()
+        }
+    }
+
+    pub fn gameboard(&self) -> &Gameboard {
+        &self.gameboard
+    }
+
+    // Replaces board with another one, e.g. when starting new game.
+    // Automatic candidates stay as they were.
+    pub fn set_gameboard(&mut self, mut gameboard: Gameboard) {
+        gameboard.set_auto_candidates(self.gameboard.auto_candidates());
+        self.solution = gameboard.solution();
+        self.gameboard = gameboard;
+        self.conflicts.clear();
+        self.flashes.clear();
+        self.last_modified = None;
+        self.gameboard_view.stop_animations();
+        self.stop_steps();
+    }
+
+    // Replaces solution used for highlighting wrong digits and solving, e.g. with
+    // official answer of puzzle that has more than one.
+    pub fn set_solution(&mut self, solution: Gameboard) {
+        self.solution = Some(solution);
+    }
+
+    pub fn set_overwrite_protect(&mut self, on: bool) {
+        self.overwrite_protect = on;
+    }
+
+    pub fn erase_mode(&self) -> bool {
+        self.erase_mode
+    }
+
+    pub fn toggle_erase_mode(&mut self) {
+        self.erase_mode = !self.erase_mode;
+        self.status = if self.erase_mode {
+            "Eraser on, click cells to clear them".to_string()
+        } else {
+            "Eraser off".to_string()
+        };
+    }
+
+    pub fn show_help(&self) -> bool {
+        self.show_help
+    }
+
+    // Returns whether view changes over time, so window must keep redrawing.
+    pub fn is_animating(&self) -> bool {
+        !self.flashes.is_empty() || self.last_modified.is_some() || self.solving ||
+            self.gameboard_view.is_animating()
+    }
+
+    // Returns changes of board made since last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<BoardEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn view_settings(&self) -> &GameboardViewSettigs {
+        &self.gameboard_view.settings
+    }
+
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    pub fn clear_all_notes(&mut self) {
+        if self.busy {
+            return;
+        }
+
+        self.edit(|gameboard| gameboard.clear_all_notes());
+        self.conflicts.clear();
+    }
+
+    // Fills all naked singles and reports how many cells got filled.
+    pub fn solve_naked_singles(&mut self) {
+        if self.busy {
+            return;
+        }
+        if !self.gameboard.is_valid() {
+            self.status = "Fix conflicts before filling singles".to_string();
+            return;
+        }
+
+        let filled = self.edit(|gameboard| gameboard.solve_naked_singles());
+        self.conflicts.clear();
+        self.status = match filled {
+            0 => "No naked singles left".to_string(),
+            1 => "Filled 1 cell".to_string(),
+            count => format!("Filled {} cells", count)
+        };
+    }
+
+    // Fills rest of board with solution, one cell after another. Digits differing
+    // from solution are replaced.
+    pub fn solve(&mut self) {
+        if self.busy {
+            return;
+        }
+
+        let solution = match self.solution.clone().ok_or(SolveError::NoSolution)
+            .or_else(|_| self.gameboard.solve_with_limit(SOLVE_NODES)) {
+
+            Ok(solution) => solution,
+            Err(SolveError::NoSolution) => {
+                self.status = "Puzzle has no solution".to_string();
+                return;
+            }
+            Err(SolveError::GaveUp) => {
+                self.status = "Solver gave up, puzzle is too hard".to_string();
+                return;
+            }
+        };
+
+        let size = self.gameboard.size();
+        let steps: VecDeque<Placement> = (0..size * size)
+            .map(|i| [i % size, i / size])
+            .filter(|&ind| !self.gameboard.is_notes_locked(ind))
+            .filter_map(|ind| {
+                let digit = solution.get_digit(ind)?;
+                if self.gameboard.get_digit(ind) == Some(digit) {
+                    return None;
+                }
+                Some(Placement { ind, digit, technique: None })
+            })
+            .collect();
+
+        if steps.is_empty() {
+            self.status = "Nothing left to solve".to_string();
+            return;
+        }
+
+        self.start_steps(steps, true);
+        self.status = "Solving, press Escape to stop".to_string();
+    }
+
+    // Starts step sequence with logical steps from current board, or writes
+    // next step of active sequence.
+    pub fn step(&mut self) {
+        if self.solving {
+            return;
+        }
+
+        if !self.busy {
+            if !self.gameboard.is_valid() {
+                self.status = "Fix conflicts before stepping".to_string();
+                return;
+            }
+
+            let steps: VecDeque<Placement> = logic::solve(&self.gameboard).steps.into_iter()
+                .map(Placement::from)
+                .collect();
+            if steps.is_empty() {
+                self.status = "No logical step found".to_string();
+                return;
+            }
+            self.start_steps(steps, false);
+        }
+
+        if let Some(step) = self.planned_steps.pop_front() {
+            self.edit_cell(step.ind, |gameboard| gameboard.set_hint(step.ind, step.digit));
+            self.selected_cell = Some(step.ind);
+            self.status = format!("Step {}/{}: {} at row {}, column {}",
+                self.total_steps - self.planned_steps.len(), self.total_steps, digit_char(step.digit),
+                step.ind[1] + 1, step.ind[0] + 1);
+            if let Some(technique) = step.technique {
+                self.status.push_str(&format!(" ({})", technique));
+            }
+        }
+        if self.planned_steps.is_empty() {
+            self.stop_steps();
+        }
+    }
+
+    // Checks board, highlighting cells in conflict until next edit.
+    pub fn check(&mut self) -> CheckReport {
+        let report = self.gameboard.check();
+        self.conflicts = report.conflicts.clone();
+        report
+    }
+
+    // Warns when givens of current puzzle can't be completed, highlighting givens
+    // that repeat. Meant to be called right after puzzle gets loaded.
+    pub fn warn_if_unsolvable(&mut self) {
+        if self.gameboard.count_solutions(1, SOLVE_NODES) != Some(0) {
+            return;
+        }
+
+        let gameboard = &self.gameboard;
+        self.conflicts = gameboard.find_conflicts().into_iter()
+            .filter(|&ind| gameboard.is_given(ind))
+            .collect();
+        self.status = "Warning: this puzzle has no solution!".to_string();
+    }
+
+    pub fn draw<G, C>(&self, c: &Context, g: &mut G, glyphs: &mut C)
+        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
+
+        self.gameboard_view.draw(self, c, g, glyphs);
+    }
+
+    pub fn handle_event<E>(&mut self, e: &E) where E: GenericEvent {
+        use piston::input::*;
+
+        if let Some(pos) = e.mouse_cursor_args() {
+            self.cursor_pos = pos;
+            self.hovered_cell = self.cell_at(pos);
+        }
+
+        if let Some(false) = e.cursor_args() {
+            self.hovered_cell = None;
+        }
+
+        if let Some(args) = e.update_args() {
+            for flash in &mut self.flashes {
+                flash.time_left -= args.dt;
+            }
+            self.flashes.retain(|flash| flash.time_left > 0.0);
+            if let Some((_, time_left)) = &mut self.last_modified {
+                *time_left -= args.dt;
+            }
+            self.last_modified = self.last_modified.filter(|&(_, time_left)| time_left > 0.0);
+            self.gameboard_view.update(args.dt);
+        }
+
+        match e.press_args() {
+            Some(Button::Keyboard(Key::LShift)) => self.shift_pressed = true,
+            Some(Button::Keyboard(Key::Escape)) if self.show_help => {
+                self.show_help = false;
+                return;
+            },
+            Some(Button::Keyboard(Key::Escape)) if self.busy => {
+                self.stop_steps();
+                self.status = "Stopped solving".to_string();
+                return;
+            },
+            Some(Button::Keyboard(key)) if !self.text_input_active() &&
+                help::action_for(key) == Some(Action::ToggleHelp) => {
+
+                self.show_help = !self.show_help;
+                return;
+            },
+            _ => ()
+        }
+
+        if let Some(Button::Keyboard(Key::LShift)) = e.release_args() {
+            self.shift_pressed = false;
+        }
+
+        if self.busy {
+            if let Some(args) = e.update_args() {
+                self.advance_solving(args.dt);
+            }
+            return;
+        }
+
+        if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
+            match self.cell_at(self.cursor_pos) {
+                Some(ind) if self.erase_mode => {
+                    self.edit_cell(ind, |gameboard| gameboard.erase(ind));
+                    self.conflicts.clear();
+                },
+                Some(ind) => self.selected_cell = Some(ind),
+                None => ()
+            }
+        }
+
+        // Scrolling cycles digit of hovered cell, or selected one if cursor is off board,
+        // going through empty cell between largest digit and 1. With overwrite protection
+        // digit can only be scrolled to or from empty cell.
+        if let Some(scroll) = e.mouse_scroll_args() {
+            if let Some(ind) = self.hovered_cell.or(self.selected_cell) {
+                let states = self.gameboard.size() as i32 + 1;
+                let digit = self.gameboard.get_digit(ind).unwrap_or(0) as i32;
+                let step = if scroll[1] > 0.0 { 1 } else if scroll[1] < 0.0 { -1 } else { 0 };
+                let next = (digit + step).rem_euclid(states) as u8;
+
+                if step != 0 && !self.gameboard.is_given(ind) {
+                    if self.overwrite_protect && digit != 0 && next != 0 {
+                        self.report_occupied(digit as u8);
+                    } else {
+                        self.set_digit(ind, next);
+                    }
+                }
+            }
+        }
+
+        if let Some(Button::Keyboard(key)) = e.press_args() {
+            if self.text_input_active() {
+                self.goto_key(key);
+                return;
+            }
+
+            let digit = GameboardController::key_digit(key, self.gameboard.size());
+            if let (Some(digit), Some(ind)) = (digit, self.selected_cell) {
+                // Digit is noted in note mode or with Shift held, but not both, and
+                // written otherwise. Noting over a digit turns it into a note, which
+                // overwrite protection refuses like replacing it.
+                let held = self.gameboard.get_digit(ind).filter(|_| !self.gameboard.is_given(ind));
+                let occupied = self.gameboard.get_digit(ind).filter(|&old| old != digit);
+                if self.shift_pressed != self.note_mode {
+                    match held {
+                        Some(old) if self.overwrite_protect => self.report_occupied(old),
+                        _ => self.edit_cell(ind, |gameboard| gameboard.note(ind, digit))
+                    }
+                } else if let (true, Some(old)) = (self.overwrite_protect, occupied) {
+                    if !self.gameboard.is_given(ind) {
+                        self.report_occupied(old);
+                    }
+                } else {
+                    self.set_digit(ind, digit);
+                }
+                self.conflicts.clear();
+            } else if let (Key::Escape, Some(ind)) = (key, self.selected_cell) {
+                // Erase digit written by player, or drop selection if there's nothing to erase.
+                if self.gameboard.get_digit(ind).is_some() && !self.gameboard.is_given(ind) &&
+                    !self.gameboard.is_notes_locked(ind) {
+
+                    self.set_digit(ind, 0);
+                } else {
+                    self.selected_cell = None;
+                }
+            } else if digit.is_none() {
+                // Letters are digits on 16x16 board, so shortcuts only get other keys.
+                if let Some(action) = help::action_for(key) {
+                    self.perform(action);
+                }
+            }
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Move(step) => self.move_selection(step),
+            Action::GoTo => {
+                self.goto_entry = Some(Vec::new());
+                self.status = "Go to row _".to_string();
+            },
+            Action::ToggleNoteMode => {
+                self.note_mode = !self.note_mode;
+                self.status = format!("Note mode {}", if self.note_mode { "on" } else { "off" });
+            },
+            Action::ToggleEraseMode => self.toggle_erase_mode(),
+            Action::ToggleNotesLock => if let Some(ind) = self.selected_cell {
+                self.gameboard.toggle_notes_lock(ind);
+            },
+            Action::ToggleShowNotes => {
+                let settings = &mut self.gameboard_view.settings;
+                settings.show_notes = !settings.show_notes;
+                self.status = format!("Notes {}", if settings.show_notes { "shown" } else { "hidden" });
+            },
+            Action::ToggleAutoCandidates => {
+                let on = !self.gameboard.auto_candidates();
+                self.edit(|gameboard| gameboard.set_auto_candidates(on));
+                self.status = format!("Auto candidates {}", if on { "on" } else { "off" });
+            },
+            // Handled before board gets the key, as they work while solving too.
+            Action::Screenshot | Action::ToggleHelp => ()
+        }
+    }
+
+    fn start_steps(&mut self, steps: VecDeque<Placement>, solving: bool) {
+        self.total_steps = steps.len();
+        self.planned_steps = steps;
+        self.solving = solving;
+        self.solve_timer = 0.0;
+        self.busy = true;
+        self.conflicts.clear();
+        self.goto_entry = None;
+    }
+
+    // Ends solving or step sequence, making board editable again.
+    fn stop_steps(&mut self) {
+        self.busy = false;
+        self.solving = false;
+        self.planned_steps.clear();
+    }
+
+    // Writes planned steps which are due after dt seconds passed.
+    fn advance_solving(&mut self, dt: f64) {
+        if !self.solving {
+            return;
+        }
+
+        self.solve_timer -= dt;
+        while self.solve_timer <= 0.0 {
+            match self.planned_steps.pop_front() {
+                Some(step) => {
+                    self.edit_cell(step.ind, |gameboard| gameboard.set_hint(step.ind, step.digit));
+                },
+                None => {
+                    self.stop_steps();
+                    self.status = if self.gameboard.is_complete() {
+                        "Solved!".to_string()
+                    } else {
+                        "Solved, except cells with locked notes".to_string()
+                    };
+                    return;
+                }
+            }
+            self.solve_timer += SOLVE_INTERVAL;
+        }
+    }
+
+    // Applies change to board, recording resulting events.
+    fn edit<F, R>(&mut self, change: F) -> R where F: FnOnce(&mut Gameboard) -> R {
+        let before = self.gameboard.clone();
+        let result = change(&mut self.gameboard);
+
+        let events = self.gameboard.changes_since(&before);
+        for event in &events {
+            if let BoardEvent::CellSet { ind, new: Some(_), .. } = *event {
+                self.gameboard_view.animate_digit(ind);
+            }
+        }
+        self.events.extend(events);
+        result
+    }
+
+    // Tells player why digit in cell wasn't replaced while overwrite protection is on.
+    fn report_occupied(&mut self, old: u8) {
+        self.status = format!("Cell holds {}, erase it with Escape first", digit_char(old));
+    }
+
+    // Changes single cell, highlighting it for a moment if anything changed.
+    fn edit_cell<F>(&mut self, ind: [usize; 2], change: F) where F: FnOnce(&mut Gameboard) {
+        let events = self.events.len();
+        self.edit(change);
+        if self.events.len() > events {
+            self.last_modified = Some((ind, self.gameboard_view.settings.last_modified_duration));
+        }
+    }
+
+    // Writes digit in cell, highlighting units it completes.
+    fn set_digit(&mut self, ind: [usize; 2], digit: u8) {
+        let old = self.gameboard.get_digit(ind);
+        self.edit_cell(ind, |gameboard| gameboard.set(ind, digit));
+        self.conflicts.clear();
+
+        if self.gameboard.get_digit(ind) != old {
+            let duration = self.gameboard_view.settings.completed_unit_flash_duration;
+            for cells in self.gameboard.completed_units(ind) {
+                self.flashes.push(Flash { cells, time_left: duration });
+            }
+        }
+    }
+
+    // Returns cell at given window position, None if position is outside of board.
+    fn cell_at(&self, pos: [f64; 2]) -> Option<[usize; 2]> {
+        let position = self.gameboard_view.settings.position;
+        let size = self.gameboard_view.settings.size;
+
+        // Find coordinates relative to top-left corner.
+        let x = pos[0] - position[0];
+        let y = pos[1] - position[1];
+
+        if x >= 0.0 && x < size && y >= 0.0 && y < size {
+            let cells = self.gameboard.size() as f64;
+            Some([(x / size * cells) as usize, (y / size * cells) as usize])
+        } else {
+            None
+        }
+    }
+
+    // Moves selection by given step, stopping at board edge.
+    // Selects top-left cell if nothing is selected.
+    fn move_selection(&mut self, step: [i32; 2]) {
+        let last = self.gameboard.size() as i32 - 1;
+
+        self.selected_cell = Some(match self.selected_cell {
+            Some(ind) => [
+                (ind[0] as i32 + step[0]).clamp(0, last) as usize,
+                (ind[1] as i32 + step[1]).clamp(0, last) as usize
+            ],
+            None => [0, 0]
+        });
+    }
+
+    // Returns whether keys are currently consumed as text, so letter shortcuts
+    // must not fire.
+    fn text_input_active(&self) -> bool {
+        self.goto_entry.is_some()
+    }
+
+    // Takes next key of cell coordinates typed after J. Escape cancels, keys
+    // which aren't digits fitting the board are ignored.
+    fn goto_key(&mut self, key: piston::input::Key) {
+        use piston::input::Key;
+
+        if key == Key::Escape {
+            self.goto_entry = None;
+            self.status.clear();
+            return;
+        }
+
+        let digit = match GameboardController::key_digit(key, self.gameboard.size()) {
+            Some(digit) => digit,
+            None => return
+        };
+
+        let entry = self.goto_entry.get_or_insert_with(Vec::new);
+        entry.push(digit);
+        if let [row, column] = entry[..] {
+            self.selected_cell = Some([(column - 1) as usize, (row - 1) as usize]);
+            self.goto_entry = None;
+            self.status = format!("Row {}, column {}", digit_char(row), digit_char(column));
+        } else {
+            self.status = format!("Go to row {}, column _", digit_char(digit));
+        }
+    }
+
+    // Returns digit entered with given key, if it fits on board of given size.
+    // Digits above 9 are entered with letters, starting with 'A' for 10.
+    fn key_digit(key: piston::input::Key, size: usize) -> Option<u8> {
+        use piston::input::Key;
+
+        let digit = match key {
+            Key::D1 => 1,
+            Key::D2 => 2,
+            Key::D3 => 3,
+            Key::D4 => 4,
+            Key::D5 => 5,
+            Key::D6 => 6,
+            Key::D7 => 7,
+            Key::D8 => 8,
+            Key::D9 => 9,
+            Key::A => 10,
+            Key::B => 11,
+            Key::C => 12,
+            Key::D => 13,
+            Key::E => 14,
+            Key::F => 15,
+            Key::G => 16,
+            _ => return None
+        };
+
+        if digit as usize <= size {
+            Some(digit)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston::input::{Button, ButtonArgs, ButtonState, Event, Input, Key, Motion, MouseButton, UpdateArgs};
+
+    const PUZZLE: &str = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+    fn controller() -> GameboardController {
+        GameboardController::new(PUZZLE.parse().unwrap(), GameboardView::new(GameboardViewSettigs::default()))
+    }
+
+    fn press(controller: &mut GameboardController, key: Key) {
+        let args = ButtonArgs { state: ButtonState::Press, button: Button::Keyboard(key), scancode: None };
+        controller.handle_event(&Event::Input(Input::Button(args), None));
+    }
+
+    #[test]
+    fn steps_block_edits_until_cancelled() {
+        let mut controller = controller();
+        controller.selected_cell = Some([2, 0]);
+
+        controller.step();
+        assert!(controller.busy);
+        let stepped = controller.selected_cell.unwrap();
+        assert_eq!(controller.gameboard.origin(stepped), Some(Origin::Hint));
+        let digit = controller.gameboard.get_digit(stepped);
+        assert_eq!(controller.drain_events(), vec![BoardEvent::CellSet { ind: stepped, old: None, new: digit }]);
+
+        press(&mut controller, Key::D1);
+        press(&mut controller, Key::Right);
+        assert_eq!(controller.gameboard.get_digit([2, 1]), None);
+        assert_eq!(controller.selected_cell, Some(stepped));
+
+        press(&mut controller, Key::Escape);
+        assert!(!controller.busy);
+        controller.selected_cell = Some([2, 1]);
+        press(&mut controller, Key::D1);
+        assert_eq!(controller.gameboard.get_digit([2, 1]), Some(1));
+    }
+
+    // Moves cursor over cell and clicks it.
+    fn click(controller: &mut GameboardController, ind: [usize; 2]) {
+        let cell_size = controller.view_settings().size / 9.0;
+        let position = controller.view_settings().position;
+        let pos = [0, 1].map(|k| position[k] + (ind[k] as f64 + 0.5) * cell_size);
+        controller.handle_event(&Event::Input(Input::Move(Motion::MouseCursor(pos)), None));
+
+        let button = Button::Mouse(MouseButton::Left);
+        let args = ButtonArgs { state: ButtonState::Press, button, scancode: None };
+        controller.handle_event(&Event::Input(Input::Button(args), None));
+    }
+
+    #[test]
+    fn eraser_clears_clicked_cells() {
+        let mut controller = controller();
+        controller.gameboard.set([2, 0], 4);
+        controller.gameboard.note([3, 0], 1);
+
+        press(&mut controller, Key::X);
+        assert!(controller.erase_mode());
+        click(&mut controller, [2, 0]);
+        click(&mut controller, [3, 0]);
+        click(&mut controller, [0, 0]);
+        assert_eq!(controller.gameboard.get_digit([2, 0]), None);
+        assert!(!controller.gameboard.get_notes([3, 0]).contains(&true));
+        assert_eq!(controller.gameboard.get_digit([0, 0]), Some(5));
+        assert_eq!(controller.selected_cell, None);
+
+        press(&mut controller, Key::X);
+        click(&mut controller, [3, 0]);
+        assert_eq!(controller.selected_cell, Some([3, 0]));
+    }
+
+    #[test]
+    fn unsolvable_puzzle_is_reported() {
+        let mut controller = controller();
+        controller.warn_if_unsolvable();
+        assert!(controller.conflicts.is_empty());
+
+        // Second 5 in first row.
+        let repeated = PUZZLE.replacen("53.", "535", 1);
+        controller.set_gameboard(repeated.parse().unwrap());
+        controller.warn_if_unsolvable();
+        assert_eq!(controller.conflicts, vec![[0, 0], [2, 0]]);
+        assert!(controller.status().contains("no solution"));
+        assert_eq!(controller.gameboard().get_digit([2, 0]), Some(5));
+    }
+
+    #[test]
+    fn selection_moves_within_board() {
+        let mut controller = controller();
+        press(&mut controller, Key::Right);
+        assert_eq!(controller.selected_cell, Some([0, 0]));
+
+        press(&mut controller, Key::A);
+        press(&mut controller, Key::W);
+        assert_eq!(controller.selected_cell, Some([0, 0]));
+        press(&mut controller, Key::D);
+        press(&mut controller, Key::S);
+        press(&mut controller, Key::Down);
+        assert_eq!(controller.selected_cell, Some([1, 2]));
+
+        for _ in 0..10 {
+            press(&mut controller, Key::Right);
+            press(&mut controller, Key::Down);
+        }
+        assert_eq!(controller.selected_cell, Some([8, 8]));
+        press(&mut controller, Key::Left);
+        press(&mut controller, Key::Up);
+        assert_eq!(controller.selected_cell, Some([7, 7]));
+
+        // On 16x16 board D writes digit, while W and arrows still move.
+        controller.set_gameboard(Gameboard::with_order(4));
+        controller.selected_cell = Some([0, 0]);
+        press(&mut controller, Key::D);
+        press(&mut controller, Key::S);
+        assert_eq!(controller.gameboard.get_digit([0, 0]), Some(13));
+        assert_eq!(controller.selected_cell, Some([0, 1]));
+        press(&mut controller, Key::Right);
+        assert_eq!(controller.selected_cell, Some([1, 1]));
+    }
+
+    #[test]
+    fn scrolling_respects_overwrite_protection() {
+        let mut controller = controller();
+        controller.set_overwrite_protect(true);
+        controller.selected_cell = Some([2, 0]);
+        let scroll = |controller: &mut GameboardController, step: f64| {
+            controller.handle_event(&Event::Input(Input::Move(Motion::MouseScroll([0.0, step])), None));
+        };
+
+        scroll(&mut controller, 1.0);
+        assert_eq!(controller.gameboard.get_digit([2, 0]), Some(1));
+        scroll(&mut controller, 1.0);
+        assert_eq!(controller.gameboard.get_digit([2, 0]), Some(1));
+        assert!(controller.status().starts_with("Cell holds 1"));
+        scroll(&mut controller, -1.0);
+        assert_eq!(controller.gameboard.get_digit([2, 0]), None);
+    }
+
+    #[test]
+    fn noting_respects_overwrite_protection() {
+        let mut controller = controller();
+        controller.set_overwrite_protect(true);
+        controller.selected_cell = Some([2, 0]);
+        press(&mut controller, Key::D5);
+
+        press(&mut controller, Key::N);
+        press(&mut controller, Key::D3);
+        assert_eq!(controller.gameboard.get_digit([2, 0]), Some(5));
+        assert!(controller.status().starts_with("Cell holds 5"));
+        assert!(!controller.gameboard.get_notes([2, 0])[2]);
+
+        // Empty cell is noted as usual.
+        controller.selected_cell = Some([3, 0]);
+        press(&mut controller, Key::D3);
+        assert!(controller.gameboard.get_notes([3, 0])[2]);
+    }
+
+    #[test]
+    fn last_modified_cell_fades_out() {
+        let mut controller = controller();
+        controller.selected_cell = Some([0, 0]);
+        press(&mut controller, Key::D1);
+        assert_eq!(controller.last_modified, None);
+
+        controller.selected_cell = Some([2, 0]);
+        press(&mut controller, Key::D1);
+        assert_eq!(controller.last_modified, Some(([2, 0], 1.0)));
+
+        controller.handle_event(&Event::Loop(UpdateArgs { dt: 0.6 }.into()));
+        assert!(controller.last_modified.is_some());
+        controller.handle_event(&Event::Loop(UpdateArgs { dt: 0.6 }.into()));
+        assert_eq!(controller.last_modified, None);
+    }
+
+    #[test]
+    fn solve_fills_board_over_time() {
+        let mut controller = controller();
+        controller.solve();
+        assert!(controller.busy && controller.is_animating());
+        assert!(controller.planned_steps.iter().all(|placement| placement.technique.is_none()));
+
+        controller.handle_event(&Event::Loop(UpdateArgs { dt: 10.0 }.into()));
+        assert!(!controller.busy);
+        assert!(controller.gameboard.is_complete());
+        assert_eq!(controller.drain_events().last(), Some(&BoardEvent::Solved));
+    }
+
+    #[test]
+    fn digit_animation_fades_in_with_updates() {
+        let mut view = GameboardView::new(GameboardViewSettigs::default());
+        view.animate_digit([1, 2]);
+        assert_eq!(view.digit_progress([1, 2]), 0.0);
+        assert_eq!(view.digit_progress([2, 2]), 1.0);
+
+        view.update(0.075);
+        assert!((view.digit_progress([1, 2]) - 0.5).abs() < 1e-9);
+
+        view.update(1.0);
+        assert!(!view.is_animating());
+        assert_eq!(view.digit_progress([1, 2]), 1.0);
+    }
+}
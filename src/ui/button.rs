@@ -3,14 +3,17 @@ use piston::generic_event::GenericEvent;
 
 pub struct Button {
     text: String,
-    hovered: bool
+    hovered: bool,
+    // Whether mode toggled by button is on.
+    active: bool
 }
 
 impl Button {
     pub fn new(text: String) -> Self {
         Self {
             text,
-            hovered: false
+            hovered: false,
+            active: false
         }
     }
 }
@@ -21,6 +24,7 @@ pub struct ButtonViewSettings
     pub size: [f64; 2],
     pub background_color: Color,
     pub hovered_background_color: Color,
+    pub active_background_color: Color,
     pub border_color: Color,
     pub border_radius: f64,
     pub text_color: Color,
@@ -34,6 +38,7 @@ impl ButtonViewSettings {
             size,
             background_color: [0.8, 0.8, 1.0, 1.0],
             hovered_background_color: [0.9, 0.9, 1.0, 1.0],
+            active_background_color: [0.6, 0.6, 1.0, 1.0],
             border_color: [0.0, 0.0, 0.2, 1.0],
             border_radius: 2.0,
             text_color: [0.0, 0.0, 1.0, 1.0],
@@ -57,14 +62,16 @@ impl ButtonView {
         where G: Graphics, C: CharacterCache<Texture = G::Texture> {
         use graphics::*;
 
-        let ref settings = self.settings;
+        let settings = &self.settings;
 
         // Draw button background.
         let button_rect = [
             settings.position[0], settings.position[1],
             settings.size[0], settings.size[1]
         ];
-        Rectangle::new(if button.hovered {
+        Rectangle::new(if button.active {
+                settings.active_background_color
+            } else if button.hovered {
                 settings.hovered_background_color
             } else {
                 settings.background_color
@@ -104,6 +111,11 @@ impl ButtonController {
         }
     }
 
+    // Shows button as pressed while its mode is on.
+    pub fn set_active(&mut self, on: bool) {
+        self.button.active = on;
+    }
+
     pub fn draw<G, C>(&mut self, c: &Context, g: &mut G, glyphs: &mut C)
         where G: Graphics, C: CharacterCache<Texture = G::Texture> {
 
@@ -121,13 +133,8 @@ impl ButtonController {
             let (x, y) = (self.cursor_pos[0], self.cursor_pos[1]);
             let position = self.button_view.settings.position;
             let size = self.button_view.settings.size;
-            if x >= position[0] && x <= position[0] + size[0] &&
-                y >= position[1] && y <= position[1] + size[1] {
-
-                self.button.hovered = true;
-            } else {
-                self.button.hovered = false;
-            }
+            self.button.hovered = x >= position[0] && x <= position[0] + size[0] &&
+                y >= position[1] && y <= position[1] + size[1];
         }
 
         if let Some(Button::Mouse(MouseButton::Left)) = e.press_args() {
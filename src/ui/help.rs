@@ -0,0 +1,162 @@
+use graphics::{Graphics, character::CharacterCache, Context, types::Color};
+use piston::input::Key;
+
+// Action triggered by shortcut key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    // Moves selection by given column and row step.
+    Move([i32; 2]),
+    GoTo,
+    ToggleNoteMode,
+    ToggleEraseMode,
+    ToggleNotesLock,
+    ToggleShowNotes,
+    ToggleAutoCandidates,
+    Screenshot,
+    ToggleHelp
+}
+
+// Line of help overlay, with keys bound to its actions. Entries without
+// bindings describe input handled separately, like digits or mouse.
+pub struct Shortcut {
+    pub keys: &'static str,
+    pub description: &'static str,
+    bindings: &'static [(Key, Action)]
+}
+
+// Every input the game reacts to, in order shown by help overlay. Key handling
+// looks actions up here, so help can't get out of date.
+pub const SHORTCUTS: &[Shortcut] = &[
+    Shortcut { keys: "1-9, A-G", description: "Write digit (A-G on 16x16)", bindings: &[] },
+    Shortcut { keys: "Shift+digit", description: "Note digit, or write in note mode", bindings: &[] },
+    Shortcut { keys: "N", description: "Toggle note mode", bindings: &[(Key::N, Action::ToggleNoteMode)] },
+    Shortcut {
+        keys: "X",
+        description: "Toggle eraser for mouse clicks",
+        bindings: &[(Key::X, Action::ToggleEraseMode)]
+    },
+    Shortcut { keys: "Mouse wheel", description: "Cycle digit under cursor", bindings: &[] },
+    Shortcut { keys: "Escape", description: "Erase, deselect, stop solving", bindings: &[] },
+    Shortcut {
+        keys: "Arrows, WASD",
+        // A and D are digits on 16x16 board, which take precedence.
+        description: "Move selection, not A/D on 16x16",
+        bindings: &[
+            (Key::Left, Action::Move([-1, 0])), (Key::A, Action::Move([-1, 0])),
+            (Key::Right, Action::Move([1, 0])), (Key::D, Action::Move([1, 0])),
+            (Key::Up, Action::Move([0, -1])), (Key::W, Action::Move([0, -1])),
+            (Key::Down, Action::Move([0, 1])), (Key::S, Action::Move([0, 1]))
+        ]
+    },
+    Shortcut { keys: "J row column", description: "Jump to cell", bindings: &[(Key::J, Action::GoTo)] },
+    Shortcut { keys: "L", description: "Lock notes of cell", bindings: &[(Key::L, Action::ToggleNotesLock)] },
+    Shortcut { keys: "H", description: "Hide or show notes", bindings: &[(Key::H, Action::ToggleShowNotes)] },
+    Shortcut {
+        keys: "P",
+        description: "Toggle automatic candidates",
+        bindings: &[(Key::P, Action::ToggleAutoCandidates)]
+    },
+    Shortcut { keys: "F12", description: "Save screenshot", bindings: &[(Key::F12, Action::Screenshot)] },
+    Shortcut {
+        keys: "F1, ?",
+        description: "Show or hide this help",
+        bindings: &[(Key::F1, Action::ToggleHelp), (Key::Slash, Action::ToggleHelp)]
+    }
+];
+
+// Returns action bound to key.
+pub fn action_for(key: Key) -> Option<Action> {
+    SHORTCUTS.iter()
+        .flat_map(|shortcut| shortcut.bindings)
+        .find(|(bound, _)| *bound == key)
+        .map(|&(_, action)| action)
+}
+
+pub struct HelpViewSettings {
+    // Position of top-left corner of overlay.
+    pub position: [f64; 2],
+    // Size along horizontal and vertical edge.
+    pub size: f64,
+    pub background_color: Color,
+    pub text_color: Color,
+    pub font_size: u32,
+    // Distance between baselines of consecutive lines.
+    pub line_height: f64,
+    // Distance from overlay edge to text, and between key and description columns.
+    pub padding: f64
+}
+
+impl HelpViewSettings {
+    pub fn new(position: [f64; 2], size: f64) -> Self {
+        Self {
+            position,
+            size,
+            background_color: [1.0, 1.0, 1.0, 0.93],
+            text_color: [0.0, 0.0, 0.2, 1.0],
+            font_size: 12,
+            line_height: 20.0,
+            padding: 12.0
+        }
+    }
+}
+
+// Draws list of shortcuts over the board.
+pub struct HelpView {
+    settings: HelpViewSettings
+}
+
+impl HelpView {
+    pub fn new(settings: HelpViewSettings) -> Self {
+        Self {
+            settings
+        }
+    }
+
+    pub fn draw<G, C>(&mut self, c: &Context, g: &mut G, glyphs: &mut C)
+        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
+        use graphics::*;
+
+        let settings = &self.settings;
+        let rect = [settings.position[0], settings.position[1], settings.size, settings.size];
+        Rectangle::new(settings.background_color)
+            .draw(rect, &c.draw_state, c.transform, g);
+
+        // Key column is as wide as the widest key description.
+        let key_width = SHORTCUTS.iter()
+            .filter_map(|shortcut| glyphs.width(settings.font_size, shortcut.keys).ok())
+            .fold(0.0, f64::max);
+
+        let text = Text::new_color(settings.text_color, settings.font_size).round();
+        for (k, shortcut) in SHORTCUTS.iter().enumerate() {
+            let x = settings.position[0] + settings.padding;
+            let y = settings.position[1] + settings.padding + settings.line_height * (k + 1) as f64;
+
+            text.draw(shortcut.keys, glyphs, &c.draw_state, c.transform.trans(x, y), g)
+                .map_err(|_| "Failed to render text!")
+                .unwrap();
+            text.draw(shortcut.description, glyphs, &c.draw_state,
+                c.transform.trans(x + key_width + settings.padding, y), g)
+                .map_err(|_| "Failed to render text!")
+                .unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_has_one_action() {
+        let keys: Vec<Key> = SHORTCUTS.iter()
+            .flat_map(|shortcut| shortcut.bindings)
+            .map(|&(key, _)| key)
+            .collect();
+
+        for (k, key) in keys.iter().enumerate() {
+            assert!(!keys[k + 1..].contains(key), "{:?} is bound twice", key);
+        }
+        assert_eq!(action_for(Key::F1), Some(Action::ToggleHelp));
+        assert_eq!(action_for(Key::Q), None);
+    }
+}
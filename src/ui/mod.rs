@@ -0,0 +1,7 @@
+// User interface layer: views drawing with Piston and controllers turning
+// window events into changes of the model.
+pub mod board;
+pub mod button;
+pub mod help;
+pub mod screenshot;
+pub mod status;
@@ -0,0 +1,237 @@
+use crate::ui::board::GameboardController;
+use graphics::{Context, DrawState, Graphics, ImageSize, Transformed, types::Color};
+use graphics::glyph_cache::rusttype::GlyphCache;
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+use texture::{CreateTexture, Format, TextureOp, TextureSettings, UpdateTexture};
+
+// Texture kept in main memory, so it can be sampled by `ImageGraphics`.
+pub struct ImageTexture {
+    image: RgbaImage
+}
+
+impl ImageSize for ImageTexture {
+    fn get_size(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+}
+
+impl TextureOp<()> for ImageTexture {
+    type Error = String;
+}
+
+impl CreateTexture<()> for ImageTexture {
+    fn create<S: Into<[u32; 2]>>(_factory: &mut (), _format: Format, memory: &[u8], size: S,
+        _settings: &TextureSettings) -> Result<Self, Self::Error> {
+
+        let size = size.into();
+        RgbaImage::from_raw(size[0], size[1], memory.to_vec())
+            .map(|image| Self { image })
+            .ok_or_else(|| "Texture data doesn't match its size!".into())
+    }
+}
+
+impl UpdateTexture<()> for ImageTexture {
+    fn update<O, S>(&mut self, _factory: &mut (), _format: Format, memory: &[u8], offset: O, size: S)
+        -> Result<(), Self::Error> where O: Into<[u32; 2]>, S: Into<[u32; 2]> {
+
+        let (offset, size) = (offset.into(), size.into());
+        if memory.len() < (size[0] * size[1] * 4) as usize {
+            return Err("Texture data doesn't match its size!".into());
+        }
+
+        for y in 0..size[1] {
+            for x in 0..size[0] {
+                let i = ((y * size[0] + x) * 4) as usize;
+                let pixel = Rgba([memory[i], memory[i + 1], memory[i + 2], memory[i + 3]]);
+                self.image.put_pixel(offset[0] + x, offset[1] + y, pixel);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Graphics back-end drawing into image in main memory. Triangles are filled
+// without anti-aliasing, textures are sampled with nearest texel.
+pub struct ImageGraphics {
+    image: RgbaImage
+}
+
+impl ImageGraphics {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height)
+        }
+    }
+
+    // Fills triangle given in normalized device coordinates, blending color
+    // returned for texture coordinates of every covered pixel.
+    fn fill_triangle<F>(&mut self, vertices: &[[f32; 2]], uvs: &[[f32; 2]], mut shade: F)
+        where F: FnMut([f32; 2]) -> Color {
+
+        let (width, height) = self.image.dimensions();
+        let p: Vec<[f32; 2]> = vertices.iter()
+            .map(|v| [(v[0] + 1.0) / 2.0 * width as f32, (1.0 - v[1]) / 2.0 * height as f32])
+            .collect();
+
+        let area = (p[1][0] - p[0][0]) * (p[2][1] - p[0][1]) - (p[2][0] - p[0][0]) * (p[1][1] - p[0][1]);
+        if area == 0.0 {
+            return;
+        }
+
+        let min_x = p.iter().map(|v| v[0]).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let max_x = p.iter().map(|v| v[0]).fold(f32::MIN, f32::max).ceil().min(width as f32) as u32;
+        let min_y = p.iter().map(|v| v[1]).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+        let max_y = p.iter().map(|v| v[1]).fold(f32::MIN, f32::max).ceil().min(height as f32) as u32;
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+                // Barycentric weights of pixel center.
+                let w0 = ((p[1][0] - px) * (p[2][1] - py) - (p[2][0] - px) * (p[1][1] - py)) / area;
+                let w1 = ((p[2][0] - px) * (p[0][1] - py) - (p[0][0] - px) * (p[2][1] - py)) / area;
+                let w2 = 1.0 - w0 - w1;
+                if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+                    continue;
+                }
+
+                let uv = if uvs.len() == 3 {
+                    [
+                        w0 * uvs[0][0] + w1 * uvs[1][0] + w2 * uvs[2][0],
+                        w0 * uvs[0][1] + w1 * uvs[1][1] + w2 * uvs[2][1]
+                    ]
+                } else {
+                    [0.0; 2]
+                };
+
+                let color = shade(uv);
+                let pixel = self.image.get_pixel_mut(x, y);
+                for k in 0..3 {
+                    let dst = pixel[k] as f32 / 255.0;
+                    pixel[k] = ((color[k] * color[3] + dst * (1.0 - color[3])) * 255.0).round() as u8;
+                }
+                let dst_alpha = pixel[3] as f32 / 255.0;
+                pixel[3] = ((color[3] + dst_alpha * (1.0 - color[3])) * 255.0).round() as u8;
+            }
+        }
+    }
+}
+
+impl Graphics for ImageGraphics {
+    type Texture = ImageTexture;
+
+    fn clear_color(&mut self, color: Color) {
+        let pixel = Rgba([
+            (color[0] * 255.0) as u8, (color[1] * 255.0) as u8,
+            (color[2] * 255.0) as u8, (color[3] * 255.0) as u8
+        ]);
+
+        for p in self.image.pixels_mut() {
+            *p = pixel;
+        }
+    }
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, _draw_state: &DrawState, color: &[f32; 4], mut f: F)
+        where F: FnMut(&mut dyn FnMut(&[[f32; 2]])) {
+
+        f(&mut |vertices| {
+            for triangle in vertices.chunks_exact(3) {
+                self.fill_triangle(triangle, &[], |_| *color);
+            }
+        });
+    }
+
+    fn tri_list_uv<F>(&mut self, _draw_state: &DrawState, color: &[f32; 4], texture: &ImageTexture,
+        mut f: F) where F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])) {
+
+        let (width, height) = texture.image.dimensions();
+        f(&mut |vertices, uvs| {
+            for (triangle, uv) in vertices.chunks_exact(3).zip(uvs.chunks_exact(3)) {
+                self.fill_triangle(triangle, uv, |uv| {
+                    let x = ((uv[0] * width as f32) as u32).min(width - 1);
+                    let y = ((uv[1] * height as f32) as u32).min(height - 1);
+                    let texel = texture.image.get_pixel(x, y);
+
+                    let mut shaded = *color;
+                    for (k, channel) in shaded.iter_mut().enumerate() {
+                        *channel *= texel[k] as f32 / 255.0;
+                    }
+                    shaded
+                });
+            }
+        });
+    }
+}
+
+// Renders board, as it's currently shown, into PNG file.
+pub fn export_png(gameboard_controller: &GameboardController, font: &[u8], path: &Path)
+    -> Result<(), String> {
+
+    let settings = gameboard_controller.view_settings();
+    let margin = settings.board_edge_radius.ceil();
+    let side = (settings.size + 2.0 * margin).ceil();
+
+    let mut glyphs: GlyphCache<(), ImageTexture> =
+        GlyphCache::from_bytes(font, (), TextureSettings::new())
+            .map_err(|_| "Couldn't load font!".to_string())?;
+    let mut graphics = ImageGraphics::new(side as u32, side as u32);
+
+    // Move board to top-left corner of image.
+    let c = Context::new_abs(side, side)
+        .trans(margin - settings.position[0], margin - settings.position[1]);
+
+    graphics.clear_color([1.0; 4]);
+    gameboard_controller.draw(&c, &mut graphics, &mut glyphs);
+
+    graphics.image.save(path).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::board::{GameboardView, GameboardViewSettigs};
+    use graphics::Rectangle;
+    use sudoku_rs::gameboard::Gameboard;
+
+    const FONT: &[u8] = include_bytes!("../../assets/UbuntuMono.ttf");
+
+    #[test]
+    fn rectangles_are_filled_and_blended() {
+        let mut graphics = ImageGraphics::new(20, 10);
+        let c = Context::new_abs(20.0, 10.0);
+        graphics.clear_color([1.0; 4]);
+
+        Rectangle::new([1.0, 0.0, 0.0, 1.0])
+            .draw([2.0, 2.0, 6.0, 4.0], &c.draw_state, c.transform, &mut graphics);
+        // Half transparent blue, partly over red one.
+        Rectangle::new([0.0, 0.0, 1.0, 0.5])
+            .draw([4.0, 2.0, 10.0, 4.0], &c.draw_state, c.transform, &mut graphics);
+
+        assert_eq!(graphics.image.dimensions(), (20, 10));
+        assert_eq!(graphics.image.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(graphics.image.get_pixel(2, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(graphics.image.get_pixel(7, 5), &Rgba([128, 0, 128, 255]));
+        assert_eq!(graphics.image.get_pixel(12, 3), &Rgba([128, 128, 255, 255]));
+        assert_eq!(graphics.image.get_pixel(7, 6), &Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn board_is_exported_to_png() {
+        let settings = GameboardViewSettigs::default();
+        let background = settings.background_color;
+        let controller = GameboardController::new(Gameboard::with_order(3), GameboardView::new(settings));
+        let path = std::env::temp_dir().join(format!("sudoku-rs-test-{}.png", std::process::id()));
+
+        export_png(&controller, FONT, &path).unwrap();
+        let image = image::open(&path).unwrap().to_rgba();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.dimensions(), (406, 406));
+        let expected = Rgba(background.map(|channel| (channel * 255.0).round() as u8));
+        assert_eq!(image.get_pixel(30, 30), &expected);
+    }
+}
@@ -0,0 +1,45 @@
+use graphics::{Graphics, character::CharacterCache, Context, types::Color};
+
+pub struct StatusViewSettings {
+    // Position of text baseline start.
+    pub position: [f64; 2],
+    pub text_color: Color,
+    pub font_size: u32
+}
+
+impl StatusViewSettings {
+    pub fn new(position: [f64; 2]) -> Self {
+        Self {
+            position,
+            text_color: [0.0, 0.0, 0.2, 1.0],
+            font_size: 15
+        }
+    }
+}
+
+// Draws single line of text informing player about game state.
+pub struct StatusView {
+    settings: StatusViewSettings
+}
+
+impl StatusView {
+    pub fn new(settings: StatusViewSettings) -> Self {
+        Self {
+            settings
+        }
+    }
+
+    pub fn draw<G, C>(&mut self, message: &str, c: &Context, g: &mut G, glyphs: &mut C)
+        where G: Graphics, C: CharacterCache<Texture = G::Texture> {
+        use graphics::*;
+
+        let settings = &self.settings;
+
+        let transform = c.transform.trans(settings.position[0], settings.position[1]);
+        Text::new_color(settings.text_color, settings.font_size)
+            .round()
+            .draw(message, glyphs, &c.draw_state, transform, g)
+            .map_err(|_| "Failed to render text!")
+            .unwrap();
+    }
+}